@@ -0,0 +1,328 @@
+// cache.rs
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+
+use crate::error::AniRustError;
+
+/// Storage for cached responses, keyed by request URL.
+///
+/// Backends are responsible for expiry: `get` must not return entries older
+/// than the TTL they were stored with.
+#[async_trait]
+pub trait CacheBackend: fmt::Debug + Send + Sync {
+    async fn get(&self, key: &str) -> Option<String>;
+    async fn put(&self, key: &str, value: String, ttl: Duration);
+    async fn remove(&self, key: &str);
+    async fn clear(&self);
+}
+
+/// Kind of page a cached response belongs to; each kind has its own TTL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CacheKind {
+    Home,
+    AtoZ,
+    About,
+    Category,
+    Search,
+    /// Episode list of a show that is still airing, or whose status is unknown
+    EpisodesAiring,
+    /// Episode list of a show that has finished airing
+    EpisodesFinished,
+    Servers,
+    Schedule,
+}
+
+/// Time to live of each [`CacheKind`]; `None` disables caching for that kind
+#[derive(Debug, Clone)]
+pub struct CacheTtls {
+    pub home: Option<Duration>,
+    pub atoz: Option<Duration>,
+    pub about: Option<Duration>,
+    pub category: Option<Duration>,
+    pub search: Option<Duration>,
+    pub episodes_airing: Option<Duration>,
+    pub episodes_finished: Option<Duration>,
+    pub servers: Option<Duration>,
+    pub schedule: Option<Duration>,
+}
+
+impl Default for CacheTtls {
+    fn default() -> Self {
+        CacheTtls {
+            home: Some(Duration::from_secs(5 * 60)),
+            atoz: Some(Duration::from_secs(60 * 60)),
+            about: Some(Duration::from_secs(6 * 60 * 60)),
+            category: Some(Duration::from_secs(30 * 60)),
+            search: Some(Duration::from_secs(15 * 60)),
+            episodes_airing: Some(Duration::from_secs(15 * 60)),
+            episodes_finished: Some(Duration::from_secs(24 * 60 * 60)),
+            servers: Some(Duration::from_secs(60 * 60)),
+            schedule: Some(Duration::from_secs(60 * 60)),
+        }
+    }
+}
+
+impl CacheTtls {
+    pub fn ttl(&self, kind: CacheKind) -> Option<Duration> {
+        match kind {
+            CacheKind::Home => self.home,
+            CacheKind::AtoZ => self.atoz,
+            CacheKind::About => self.about,
+            CacheKind::Category => self.category,
+            CacheKind::Search => self.search,
+            CacheKind::EpisodesAiring => self.episodes_airing,
+            CacheKind::EpisodesFinished => self.episodes_finished,
+            CacheKind::Servers => self.servers,
+            CacheKind::Schedule => self.schedule,
+        }
+    }
+}
+
+/// Response cache used by `HiAnimeRust`: a backend plus the TTLs to store with.
+#[derive(Debug)]
+pub struct ResponseCache {
+    backend: Box<dyn CacheBackend>,
+    ttls: CacheTtls,
+    // Anime ids known to have finished airing, learned from `scrape_about_anime`
+    finished: Mutex<HashSet<String>>,
+}
+
+impl ResponseCache {
+    pub fn new(backend: impl CacheBackend + 'static, ttls: CacheTtls) -> Self {
+        ResponseCache {
+            backend: Box::new(backend),
+            ttls,
+            finished: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn ttls(&self) -> &CacheTtls {
+        &self.ttls
+    }
+
+    pub async fn get(&self, kind: CacheKind, url: &str) -> Option<String> {
+        self.ttls.ttl(kind)?;
+        self.backend.get(url).await
+    }
+
+    pub async fn put(&self, kind: CacheKind, url: &str, value: String) {
+        if let Some(ttl) = self.ttls.ttl(kind) {
+            self.backend.put(url, value, ttl).await;
+        }
+    }
+
+    pub async fn clear(&self) {
+        self.backend.clear().await;
+    }
+
+    /// Records whether the anime with `anime_id` has finished airing.
+    pub fn set_finished(&self, anime_id: &str, finished: bool) {
+        let mut ids = self.finished.lock().unwrap();
+        if finished {
+            ids.insert(anime_id.to_string());
+        } else {
+            ids.remove(anime_id);
+        }
+    }
+
+    /// Kind to cache the episode list of `anime_id` as.
+    pub fn episodes_kind(&self, anime_id: &str) -> CacheKind {
+        if self.finished.lock().unwrap().contains(anime_id) {
+            CacheKind::EpisodesFinished
+        } else {
+            CacheKind::EpisodesAiring
+        }
+    }
+}
+
+/// In-memory backend that evicts the least recently used entry when full.
+#[derive(Debug)]
+pub struct MemoryCache {
+    capacity: usize,
+    entries: Mutex<HashMap<String, MemoryEntry>>,
+}
+
+#[derive(Debug)]
+struct MemoryEntry {
+    value: String,
+    expires_at: Instant,
+    last_used: Instant,
+}
+
+impl MemoryCache {
+    pub fn new(capacity: usize) -> Self {
+        MemoryCache {
+            capacity: capacity.max(1),
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait]
+impl CacheBackend for MemoryCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+
+        let entry = entries.get_mut(key)?;
+        if entry.expires_at <= now {
+            entries.remove(key);
+            return None;
+        }
+        entry.last_used = now;
+        Some(entry.value.clone())
+    }
+
+    async fn put(&self, key: &str, value: String, ttl: Duration) {
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+
+        if !entries.contains_key(key) && entries.len() >= self.capacity {
+            // Drop what has expired first, then the least recently used entry
+            entries.retain(|_, entry| entry.expires_at > now);
+            if entries.len() >= self.capacity {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.last_used)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+
+        entries.insert(
+            key.to_string(),
+            MemoryEntry {
+                value,
+                expires_at: now + ttl,
+                last_used: now,
+            },
+        );
+    }
+
+    async fn remove(&self, key: &str) {
+        self.entries.lock().unwrap().remove(key);
+    }
+
+    async fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+/// On-disk backend storing one file per entry in a directory.
+///
+/// Each file holds the expiry as a Unix timestamp on its first line, followed
+/// by the response body. Entries survive restarts of the process, and `clear`
+/// only removes files named like entries, so the directory can be shared.
+#[derive(Debug, Clone)]
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+impl DiskCache {
+    /// Uses `dir` as the cache directory, creating it if needed.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, AniRustError> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).map_err(|e| {
+            AniRustError::InvalidConfig(format!(
+                "Failed to create cache directory {}: {}",
+                dir.display(),
+                e
+            ))
+        })?;
+        Ok(DiskCache { dir })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{:x}", md5::compute(key)))
+    }
+}
+
+#[async_trait]
+impl CacheBackend for DiskCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        let path = self.path(key);
+        let content = tokio::fs::read_to_string(&path).await.ok()?;
+        let (expires_at, value) = content.split_once('\n')?;
+
+        if expires_at.parse::<u64>().ok()? <= unix_now() {
+            let _ = tokio::fs::remove_file(&path).await;
+            return None;
+        }
+        Some(value.to_string())
+    }
+
+    async fn put(&self, key: &str, value: String, ttl: Duration) {
+        let expires_at = unix_now() + ttl.as_secs();
+        let path = self.path(key);
+        // Write to a temporary file first so readers never see a partial entry. Each
+        // write gets its own name so concurrent writers never share one
+        let tmp = path.with_extension(format!(
+            "{}-{}.tmp",
+            std::process::id(),
+            TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let written = tokio::fs::write(&tmp, format!("{}\n{}", expires_at, value)).await;
+        if written.is_err() || tokio::fs::rename(&tmp, &path).await.is_err() {
+            let _ = tokio::fs::remove_file(&tmp).await;
+        }
+    }
+
+    async fn remove(&self, key: &str) {
+        let _ = tokio::fs::remove_file(self.path(key)).await;
+    }
+
+    async fn clear(&self) {
+        let Ok(mut dir) = tokio::fs::read_dir(&self.dir).await else {
+            return;
+        };
+        while let Ok(Some(entry)) = dir.next_entry().await {
+            // The directory may be shared, so leave alone what the cache did not write
+            if entry.file_name().to_str().is_some_and(is_cache_file) {
+                let _ = tokio::fs::remove_file(entry.path()).await;
+            }
+        }
+    }
+}
+
+// Entries are named after the md5 of their key, and temporary files add a
+// `.<pid>-<counter>.tmp` suffix to that name
+fn is_cache_file(name: &str) -> bool {
+    let (hash, suffix) = name.split_once('.').unwrap_or((name, ""));
+    let is_hash = hash.len() == 32
+        && hash
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b));
+    is_hash && (suffix.is_empty() || suffix.ends_with(".tmp"))
+}
+
+// Suffix of the temporary files written by `DiskCache::put`
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
@@ -0,0 +1,494 @@
+// config.rs
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::{
+    header::{self, HeaderMap, HeaderName, HeaderValue},
+    Url,
+};
+
+use crate::{
+    cache::ResponseCache,
+    env::{EnvVar, SecretConfig},
+    error::AniRustError,
+    hianime::HiAnimeRust,
+    parse::ParseMode,
+    proxy::{ProxyKind, ProxyPoolConfig, ProxySource},
+    rate_limit::{RateLimit, RateLimitConfig},
+    reporter::{ErrorReporter, WebhookReporter},
+    retry::RetryPolicy,
+    selectors::{SelectorProfile, Selectors},
+    transport::{Fetcher, ReqwestFetcher},
+};
+
+const DEFAULT_DOMAIN: &str = "https://aniwatchtv.to";
+
+/// Webhooks notified when an error of the matching kind occurs
+#[derive(Debug, Clone, Default)]
+pub struct Webhooks {
+    pub reqwest_error: Option<Url>,
+    pub no_proxies_available: Option<Url>,
+    pub failed_to_fetch_after_retries: Option<Url>,
+    pub utils_error: Option<Url>,
+    pub unknown_error: Option<Url>,
+}
+
+/// Validated settings of a `HiAnimeRust` client
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    pub retry_policy: RetryPolicy,
+    pub domains: Vec<Url>,
+    /// Headers sent with every request
+    pub headers: HeaderMap,
+    pub webhooks: Webhooks,
+    pub proxy_sources: Vec<ProxySource>,
+    pub proxy_pool: ProxyPoolConfig,
+    /// Interval of the background proxy list refresh, if any
+    pub proxy_refresh_interval: Option<Duration>,
+    /// URL every proxy is probed against right after `build()`, if any
+    pub proxy_probe_url: Option<Url>,
+    /// Per-host throttling of every request
+    pub rate_limit: RateLimitConfig,
+    /// What to do with pages that show signs of markup drift
+    pub parse_mode: ParseMode,
+    /// Selectors pages are parsed with
+    pub selectors: Arc<Selectors>,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        ClientConfig {
+            retry_policy: RetryPolicy::default(),
+            domains: vec![Url::parse(DEFAULT_DOMAIN).unwrap()],
+            headers: HeaderMap::new(),
+            webhooks: Webhooks::default(),
+            proxy_sources: Vec::new(),
+            proxy_pool: ProxyPoolConfig::default(),
+            proxy_refresh_interval: None,
+            proxy_probe_url: None,
+            rate_limit: RateLimitConfig::default(),
+            parse_mode: ParseMode::default(),
+            selectors: Selectors::shared_default(),
+        }
+    }
+}
+
+/// Builder for [`HiAnimeRust`].
+///
+/// Settings left unset fall back to `.env` and the process environment, then
+/// to the built-in defaults. Everything is validated in [`HiAnimeRustBuilder::build`].
+#[derive(Debug)]
+pub struct HiAnimeRustBuilder {
+    retry_policy: Option<RetryPolicy>,
+    max_retries_attempts: Option<usize>,
+    timeout: Option<Duration>,
+    domains: Option<Vec<Url>>,
+    headers: HeaderMap,
+    webhooks: Webhooks,
+    proxy_sources: Option<Vec<ProxySource>>,
+    proxy_pool: ProxyPoolConfig,
+    proxy_refresh_interval: Option<Duration>,
+    proxy_probe_url: Option<Url>,
+    rate_limit: RateLimitConfig,
+    parse_mode: ParseMode,
+    selector_profile: Option<SelectorProfile>,
+    fetcher: Option<Arc<dyn Fetcher>>,
+    reporter: Option<Arc<dyn ErrorReporter>>,
+    cache: Option<Arc<ResponseCache>>,
+    env_fallback: bool,
+}
+
+impl Default for HiAnimeRustBuilder {
+    fn default() -> Self {
+        HiAnimeRustBuilder {
+            retry_policy: None,
+            max_retries_attempts: None,
+            timeout: None,
+            domains: None,
+            headers: HeaderMap::new(),
+            webhooks: Webhooks::default(),
+            proxy_sources: None,
+            proxy_pool: ProxyPoolConfig::default(),
+            proxy_refresh_interval: None,
+            proxy_probe_url: None,
+            rate_limit: RateLimitConfig::default(),
+            parse_mode: ParseMode::default(),
+            selector_profile: None,
+            fetcher: None,
+            reporter: None,
+            cache: None,
+            env_fallback: true,
+        }
+    }
+}
+
+impl HiAnimeRustBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder holding the values of a legacy [`SecretConfig`].
+    ///
+    /// Like `HiAnimeRust::new(Some(secret))`, the environment is not consulted.
+    pub fn from_secret(secret: SecretConfig) -> Result<Self, AniRustError> {
+        let mut builder = Self::new().env_fallback(false);
+
+        if let Some(value) = non_empty(&secret.max_retries_attempts) {
+            builder.max_retries_attempts = Some(parse_retries(value)?);
+        }
+        if let Some(value) = non_empty(&secret.hianime_domains) {
+            builder.domains = Some(parse_domains(value)?);
+        }
+
+        builder.webhooks = Webhooks {
+            reqwest_error: parse_webhook(&secret.reqwest_error_webhook)?,
+            no_proxies_available: parse_webhook(&secret.no_proxies_available_error_webhook)?,
+            failed_to_fetch_after_retries: parse_webhook(
+                &secret.failed_to_fetch_after_retries_error_webhook,
+            )?,
+            utils_error: parse_webhook(&secret.utils_error_webhook)?,
+            unknown_error: parse_webhook(&secret.unknown_error_webhook)?,
+        };
+
+        builder.proxy_sources = Some(proxy_sources(
+            non_empty(&secret.sock5_url),
+            non_empty(&secret.sock4_url),
+            non_empty(&secret.http_url),
+        ));
+
+        for (name, value) in [
+            (header::USER_AGENT, &secret.user_agent_header),
+            (header::ACCEPT_ENCODING, &secret.accept_encoding_header),
+            (header::ACCEPT, &secret.accept_header),
+        ] {
+            if let Some(value) = non_empty(value) {
+                builder.headers.insert(name, parse_header(value)?);
+            }
+        }
+
+        Ok(builder)
+    }
+
+    /// Retry policy used by every request, unless overridden per call
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Shorthand for the `max_attempts` of the retry policy
+    pub fn max_retries_attempts(mut self, attempts: usize) -> Self {
+        self.max_retries_attempts = Some(attempts);
+        self
+    }
+
+    /// Shorthand for the `attempt_timeout` of the retry policy
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// HiAnime mirrors, tried in order
+    pub fn domains(mut self, domains: Vec<Url>) -> Self {
+        self.domains = Some(domains);
+        self
+    }
+
+    pub fn domain(mut self, domain: Url) -> Self {
+        self.domains.get_or_insert_with(Vec::new).push(domain);
+        self
+    }
+
+    /// Headers sent with every request, on top of the ones from the environment
+    pub fn headers(mut self, headers: HeaderMap) -> Self {
+        self.headers.extend(headers);
+        self
+    }
+
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    pub fn webhooks(mut self, webhooks: Webhooks) -> Self {
+        self.webhooks = webhooks;
+        self
+    }
+
+    pub fn proxy_sources(mut self, sources: Vec<ProxySource>) -> Self {
+        self.proxy_sources = Some(sources);
+        self
+    }
+
+    pub fn proxy_source(mut self, source: ProxySource) -> Self {
+        self.proxy_sources.get_or_insert_with(Vec::new).push(source);
+        self
+    }
+
+    pub fn proxy_pool(mut self, config: ProxyPoolConfig) -> Self {
+        self.proxy_pool = config;
+        self
+    }
+
+    /// Reloads the proxy lists every `interval` in the background
+    pub fn proxy_refresh_interval(mut self, interval: Duration) -> Self {
+        self.proxy_refresh_interval = Some(interval);
+        self
+    }
+
+    /// Probes every proxy against `url` in the background once the client is built
+    pub fn proxy_probe_url(mut self, url: Url) -> Self {
+        self.proxy_probe_url = Some(url);
+        self
+    }
+
+    /// Per-host rate limits, replacing the default of [`RateLimit::default`] for every host
+    pub fn rate_limit(mut self, config: RateLimitConfig) -> Self {
+        self.rate_limit = config;
+        self
+    }
+
+    /// Overrides the rate limit of a single host, e.g. `megacloud.tv`
+    pub fn host_rate_limit(mut self, host: &str, limit: RateLimit) -> Self {
+        self.rate_limit = self.rate_limit.host(host, limit);
+        self
+    }
+
+    /// Sends every request through `fetcher` instead of the default `ReqwestFetcher`
+    pub fn fetcher(mut self, fetcher: Arc<dyn Fetcher>) -> Self {
+        self.fetcher = Some(fetcher);
+        self
+    }
+
+    /// Receives the errors returned by the client, instead of the default
+    /// [`WebhookReporter`] built from the configured webhooks
+    pub fn error_reporter(mut self, reporter: Arc<dyn ErrorReporter>) -> Self {
+        self.reporter = Some(reporter);
+        self
+    }
+
+    /// How pages with missing selectors or empty fields are handled; lenient by default
+    pub fn parse_mode(mut self, mode: ParseMode) -> Self {
+        self.parse_mode = mode;
+        self
+    }
+
+    /// Parses pages with `profile`, e.g. one loaded with [`SelectorProfile::from_file`]
+    pub fn selector_profile(mut self, profile: SelectorProfile) -> Self {
+        self.selector_profile = Some(profile);
+        self
+    }
+
+    /// Caches responses in `cache`; nothing is cached by default
+    pub fn cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// Whether unset settings are read from `.env` and the process environment
+    pub fn env_fallback(mut self, enabled: bool) -> Self {
+        self.env_fallback = enabled;
+        self
+    }
+
+    /// Validates the settings and builds the resolved [`ClientConfig`].
+    pub fn build_config(&self) -> Result<ClientConfig, AniRustError> {
+        let env = |var: EnvVar| {
+            if self.env_fallback {
+                var.read_env()
+            } else {
+                None
+            }
+        };
+
+        let mut retry_policy = self.retry_policy.clone().unwrap_or_default();
+        match self.max_retries_attempts {
+            Some(attempts) => retry_policy.max_attempts = attempts,
+            None if self.retry_policy.is_none() => {
+                if let Some(value) = env(EnvVar::MAX_RETRIES_ATTEMPTS) {
+                    retry_policy.max_attempts = parse_retries(&value)?;
+                }
+            }
+            None => {}
+        }
+        if let Some(timeout) = self.timeout {
+            retry_policy.attempt_timeout = timeout;
+        }
+        retry_policy.validate()?;
+
+        let domains = match &self.domains {
+            Some(domains) => domains.clone(),
+            None => match env(EnvVar::HIANIME_DOMAINS) {
+                Some(value) => parse_domains(&value)?,
+                None => vec![Url::parse(DEFAULT_DOMAIN).unwrap()],
+            },
+        };
+        if domains.is_empty() {
+            return Err(AniRustError::InvalidConfig(
+                "at least one domain is required".to_string(),
+            ));
+        }
+        for domain in &domains {
+            validate_http_url(domain, "domain")?;
+        }
+
+        let mut headers = HeaderMap::new();
+        for (name, var) in [
+            (header::USER_AGENT, EnvVar::USER_AGENT_HEADER),
+            (header::ACCEPT_ENCODING, EnvVar::ACCEPT_ENCODING_HEADER),
+            (header::ACCEPT, EnvVar::ACCEPT_HEADER),
+        ] {
+            if let Some(value) = env(var) {
+                headers.insert(name, parse_header(&value)?);
+            }
+        }
+        headers.extend(self.headers.clone());
+
+        let or_env = |url: &Option<Url>, var: EnvVar| -> Result<Option<Url>, AniRustError> {
+            match url {
+                Some(url) => Ok(Some(url.clone())),
+                None => parse_webhook(&env(var).unwrap_or_default()),
+            }
+        };
+        let webhooks = Webhooks {
+            reqwest_error: or_env(&self.webhooks.reqwest_error, EnvVar::REQWEST_ERROR_WEBHOOK)?,
+            no_proxies_available: or_env(
+                &self.webhooks.no_proxies_available,
+                EnvVar::NO_PROXIES_AVAILABLE_ERROR_WEBHOOK,
+            )?,
+            failed_to_fetch_after_retries: or_env(
+                &self.webhooks.failed_to_fetch_after_retries,
+                EnvVar::FAILED_TO_FETCH_AFTER_RETRIES_ERROR_WEBHOOK,
+            )?,
+            utils_error: or_env(&self.webhooks.utils_error, EnvVar::UTILS_ERROR_WEBHOOK)?,
+            unknown_error: or_env(&self.webhooks.unknown_error, EnvVar::UNKNOWN_ERROR_WEBHOOK)?,
+        };
+        for url in [
+            &webhooks.reqwest_error,
+            &webhooks.no_proxies_available,
+            &webhooks.failed_to_fetch_after_retries,
+            &webhooks.utils_error,
+            &webhooks.unknown_error,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            validate_http_url(url, "webhook")?;
+        }
+
+        let proxy_sources = match &self.proxy_sources {
+            Some(sources) => sources.clone(),
+            None => proxy_sources(
+                env(EnvVar::SOCK5_URL).as_deref(),
+                env(EnvVar::SOCK4_URL).as_deref(),
+                env(EnvVar::HTTP_URL).as_deref(),
+            ),
+        };
+
+        if let Some(url) = &self.proxy_probe_url {
+            validate_http_url(url, "proxy probe url")?;
+        }
+        if self.proxy_refresh_interval.is_some_and(|i| i.is_zero()) {
+            return Err(AniRustError::InvalidConfig(
+                "proxy_refresh_interval must be greater than zero".to_string(),
+            ));
+        }
+
+        self.rate_limit.validate()?;
+
+        let selectors = match &self.selector_profile {
+            Some(profile) => Arc::new(Selectors::new(profile.clone())?),
+            None => Selectors::shared_default(),
+        };
+
+        Ok(ClientConfig {
+            retry_policy,
+            domains,
+            headers,
+            webhooks,
+            proxy_sources,
+            proxy_pool: self.proxy_pool.clone(),
+            proxy_refresh_interval: self.proxy_refresh_interval,
+            proxy_probe_url: self.proxy_probe_url.clone(),
+            rate_limit: self.rate_limit.clone(),
+            parse_mode: self.parse_mode,
+            selectors,
+        })
+    }
+
+    /// Validates the settings, loads the proxy lists and builds the client.
+    pub async fn build(self) -> Result<HiAnimeRust, AniRustError> {
+        let config = self.build_config()?;
+        let fetcher = self
+            .fetcher
+            .unwrap_or_else(|| Arc::new(ReqwestFetcher::new()));
+        let reporter = self
+            .reporter
+            .unwrap_or_else(|| Arc::new(WebhookReporter::new(config.webhooks.clone())));
+
+        Ok(HiAnimeRust::from_config(config, fetcher, reporter, self.cache).await)
+    }
+}
+
+fn non_empty(value: &str) -> Option<&str> {
+    let value = value.trim();
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+fn parse_retries(value: &str) -> Result<usize, AniRustError> {
+    value.trim().parse::<usize>().map_err(|e| {
+        AniRustError::InvalidConfig(format!("invalid max_retries_attempts {:?}: {}", value, e))
+    })
+}
+
+fn parse_domains(value: &str) -> Result<Vec<Url>, AniRustError> {
+    value
+        .split(',')
+        .filter_map(non_empty)
+        .map(|domain| {
+            Url::parse(domain).map_err(|e| {
+                AniRustError::InvalidConfig(format!("invalid domain {:?}: {}", domain, e))
+            })
+        })
+        .collect()
+}
+
+fn parse_webhook(value: &str) -> Result<Option<Url>, AniRustError> {
+    non_empty(value)
+        .map(|url| {
+            Url::parse(url).map_err(|e| {
+                AniRustError::InvalidConfig(format!("invalid webhook {:?}: {}", url, e))
+            })
+        })
+        .transpose()
+}
+
+fn parse_header(value: &str) -> Result<HeaderValue, AniRustError> {
+    HeaderValue::from_str(value)
+        .map_err(|e| AniRustError::InvalidConfig(format!("invalid header {:?}: {}", value, e)))
+}
+
+fn validate_http_url(url: &Url, what: &str) -> Result<(), AniRustError> {
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+        return Err(AniRustError::InvalidConfig(format!(
+            "{} must be an http(s) url: {}",
+            what, url
+        )));
+    }
+    Ok(())
+}
+
+fn proxy_sources(sock5: Option<&str>, sock4: Option<&str>, http: Option<&str>) -> Vec<ProxySource> {
+    [
+        (sock5, ProxyKind::Socks5),
+        (sock4, ProxyKind::Socks4),
+        (http, ProxyKind::Http),
+    ]
+    .into_iter()
+    .filter_map(|(location, kind)| location.map(|l| ProxySource::from_location(l, kind)))
+    .collect()
+}
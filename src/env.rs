@@ -2,17 +2,10 @@
 
 use std::env;
 use std::fmt;
-use std::sync::Mutex;
 
 use dotenvy::dotenv;
-use lazy_static::lazy_static;
-
-use crate::error::AniRustError;
-
-lazy_static! {
-    pub static ref SECRET: Mutex<Option<SecretConfig>> = Mutex::new(None);
-}
 
+#[allow(non_camel_case_types)]
 #[derive(Debug)]
 pub enum EnvVar {
     MAX_RETRIES_ATTEMPTS,
@@ -48,6 +41,7 @@ pub struct SecretConfig {
 }
 
 impl SecretConfig {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         max_retries_attempts: String,
         reqwest_error_webhook: String,
@@ -79,26 +73,11 @@ impl SecretConfig {
             accept_header,
         }
     }
-
-    pub fn webhook_url(&self, error: AniRustError) -> String {
-        match error {
-            AniRustError::ReqwestError(_) => self.reqwest_error_webhook.clone(),
-            AniRustError::HyperError(_) => self.reqwest_error_webhook.clone(),
-            AniRustError::RegexError(_) => self.utils_error_webhook.clone(),
-            AniRustError::NoProxiesAvailable => self.no_proxies_available_error_webhook.clone(),
-            AniRustError::FailedToFetchAfterRetries => {
-                self.failed_to_fetch_after_retries_error_webhook.clone()
-            }
-            AniRustError::ParseIntError(_) => self.utils_error_webhook.clone(),
-            AniRustError::NoDomainExists(_) => String::new(),
-            AniRustError::UnknownError(_) => self.unknown_error_webhook.clone(),
-        }
-    }
 }
 
 impl EnvVar {
     // Convert EnvVar to the corresponding environment variable key
-    fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &'static str {
         match self {
             EnvVar::MAX_RETRIES_ATTEMPTS => "MAX_RETRIES_ATTEMPTS",
             EnvVar::REQWEST_ERROR_WEBHOOK => "REQWEST_ERROR_WEBHOOK",
@@ -118,45 +97,15 @@ impl EnvVar {
         }
     }
 
-    // Fetch the environment variable value and return it as a String
-    pub fn get_config(&self) -> String {
-        let key = self.as_str();
-
-        // Attempt to get the value from the SECRET
-        let secret = SECRET.lock().unwrap();
-
-        if let Some(s) = &*secret {
-            match self {
-                EnvVar::MAX_RETRIES_ATTEMPTS => return s.max_retries_attempts.clone(),
-                EnvVar::REQWEST_ERROR_WEBHOOK => return s.reqwest_error_webhook.clone(),
-                EnvVar::NO_PROXIES_AVAILABLE_ERROR_WEBHOOK => {
-                    return s.no_proxies_available_error_webhook.clone()
-                }
-                EnvVar::FAILED_TO_FETCH_AFTER_RETRIES_ERROR_WEBHOOK => {
-                    return s.failed_to_fetch_after_retries_error_webhook.clone()
-                }
-                EnvVar::UTILS_ERROR_WEBHOOK => return s.utils_error_webhook.clone(),
-                EnvVar::HTTP_URL => return s.http_url.clone(),
-                EnvVar::SOCK4_URL => return s.sock4_url.clone(),
-                EnvVar::SOCK5_URL => return s.sock5_url.clone(),
-                EnvVar::HIANIME_DOMAINS => return s.hianime_domains.clone(),
-                EnvVar::USER_AGENT_HEADER => return s.user_agent_header.clone(),
-                EnvVar::ACCEPT_ENCODING_HEADER => return s.accept_encoding_header.clone(),
-                EnvVar::ACCEPT_HEADER => return s.accept_header.clone(),
-                EnvVar::UNKNOWN_ERROR_WEBHOOK => return s.unknown_error_webhook.clone(),
-            }
-        }
-        // Release the lock.
-        drop(secret);
-
-        // Load environment variables from a .env file
+    /// Reads the variable from `.env` or the process environment.
+    ///
+    /// Returns `None` when it is unset or empty.
+    pub fn read_env(&self) -> Option<String> {
         dotenv().ok();
-
-        // Fallback to environment variable if secret is not set
-        match env::var(key) {
-            Ok(val) => val,
-            Err(_) => String::new(),
-        }
+        env::var(self.as_str())
+            .ok()
+            .map(|val| val.trim().to_string())
+            .filter(|val| !val.is_empty())
     }
 }
 
@@ -1,43 +1,120 @@
-use chrono::{DateTime, Utc};
-use reqwest::blocking::Client;
-use serde_json::json;
 use std::error::Error as StdError;
 use std::fmt;
 
-use crate::env::EnvVar;
+use reqwest::{StatusCode, Url};
+
+use crate::{config::Webhooks, parse::ParseDiagnostics};
 
 /// Custom error enum to handle different types of errors
 #[derive(Debug)]
 pub enum AniRustError {
     /// Reqwest error
     ReqwestError(reqwest::Error),
-    /// Hyper error
-    HyperError(hyper::Error),
     /// Regex error
     RegexError(regex::Error),
     /// No Proxies available error
     NoProxiesAvailable,
-    /// Failed to fetch even after multiple tries error
-    FailedToFetchAfterRetries,
+    /// Every attempt to fetch `url` failed
+    FailedToFetchAfterRetries {
+        url: String,
+        attempts: usize,
+        last_error: Option<Box<AniRustError>>,
+    },
+    /// Every domain failed, with the error of each one in the order they were tried
+    AllDomainsFailed(Vec<DomainError>),
+    /// The server answered with an unexpected HTTP status
+    HttpStatus { url: String, status: StatusCode },
+    /// The page does not exist (HTTP 404)
+    NotFound { url: String },
+    /// An anti-bot challenge, such as a Cloudflare interstitial, was served instead of the page
+    AntiBotChallenge { url: String },
+    /// A selector the scraper relies on matched nothing
+    SelectorNotFound { url: String, selector: String },
+    /// The response is not in the expected format
+    InvalidResponse { url: String, reason: String },
+    /// The page was parsed, but selectors or fields it should have came back empty
+    IncompleteParse {
+        url: String,
+        diagnostics: ParseDiagnostics,
+    },
+    /// A streaming server's sources could not be extracted
+    ExtractionFailed { server: String, reason: String },
+    /// The decryption key could not be found in a streaming server's player script
+    DecryptionKeyNotFound { server: String },
     /// Parsing int error
     ParseIntError(std::num::ParseIntError),
     /// No domain exists
     NoDomainExists(String),
+    /// Invalid client configuration
+    InvalidConfig(String),
+    /// A `ReplayFetcher` has no fixture for the URL
+    NoRecordedResponse(String),
+    /// An anime, episode or category id that cannot be put in a URL
+    InvalidId { id: String, reason: String },
     /// all rest errors
     UnknownError(String),
 }
 
+/// Error returned by a single domain, see [`AniRustError::AllDomainsFailed`]
+#[derive(Debug)]
+pub struct DomainError {
+    pub domain: String,
+    pub error: AniRustError,
+}
+
 // Implement Display for AniRustError
 impl fmt::Display for AniRustError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             AniRustError::ReqwestError(err) => write!(f, "Reqwest error: {}", err),
-            AniRustError::HyperError(err) => write!(f, "Hyper error: {}", err),
             AniRustError::RegexError(err) => write!(f, "Regex error: {}", err),
             AniRustError::NoProxiesAvailable => write!(f, "No proxies available"),
-            AniRustError::FailedToFetchAfterRetries => write!(f, "Failed to fetch after retries"),
+            AniRustError::FailedToFetchAfterRetries {
+                url,
+                attempts,
+                last_error,
+            } => {
+                write!(f, "Failed to fetch {} after {} attempts", url, attempts)?;
+                match last_error {
+                    Some(err) => write!(f, ": {}", err),
+                    None => Ok(()),
+                }
+            }
+            AniRustError::AllDomainsFailed(errors) => {
+                write!(f, "All domains failed")?;
+                for (i, DomainError { domain, error }) in errors.iter().enumerate() {
+                    let separator = if i == 0 { ": " } else { ", " };
+                    write!(f, "{}{} ({})", separator, domain, error)?;
+                }
+                Ok(())
+            }
+            AniRustError::HttpStatus { url, status } => {
+                write!(f, "Unexpected HTTP status {} for {}", status, url)
+            }
+            AniRustError::NotFound { url } => write!(f, "Not found: {}", url),
+            AniRustError::AntiBotChallenge { url } => {
+                write!(f, "Anti-bot challenge served for {}", url)
+            }
+            AniRustError::SelectorNotFound { url, selector } => {
+                write!(f, "Selector `{}` matched nothing on {}", selector, url)
+            }
+            AniRustError::InvalidResponse { url, reason } => {
+                write!(f, "Invalid response from {}: {}", url, reason)
+            }
+            AniRustError::IncompleteParse { url, diagnostics } => {
+                write!(f, "Incomplete parse of {}: {}", url, diagnostics)
+            }
+            AniRustError::ExtractionFailed { server, reason } => {
+                write!(f, "Failed to extract {} sources: {}", server, reason)
+            }
+            AniRustError::DecryptionKeyNotFound { server } => {
+                write!(f, "Decryption key not found for {}", server)
+            }
             AniRustError::ParseIntError(err) => write!(f, "Failed to parse int error: {}", err),
             AniRustError::NoDomainExists(site) => write!(f, "No domain added for: {}", site),
+            AniRustError::InvalidConfig(reason) => write!(f, "Invalid configuration: {}", reason),
+            AniRustError::NoRecordedResponse(url) => write!(f, "No recorded response for {}", url),
+            AniRustError::InvalidId { id, reason } => write!(f, "Invalid id `{}`: {}", id, reason),
             AniRustError::UnknownError(err) => write!(f, "Std error occured: {}", err),
         }
     }
@@ -50,13 +127,6 @@ impl From<reqwest::Error> for AniRustError {
     }
 }
 
-// Implement From trait to convert hyper::Error to AniRustError
-impl From<hyper::Error> for AniRustError {
-    fn from(err: hyper::Error) -> Self {
-        AniRustError::HyperError(err)
-    }
-}
-
 // Implement From trait to convert regex::Error to AniRustError
 impl From<regex::Error> for AniRustError {
     fn from(err: regex::Error) -> Self {
@@ -76,73 +146,97 @@ impl StdError for AniRustError {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match self {
             AniRustError::ReqwestError(err) => Some(err),
-            AniRustError::HyperError(err) => Some(err),
             AniRustError::RegexError(err) => Some(err),
-            AniRustError::NoProxiesAvailable => None,
-            AniRustError::FailedToFetchAfterRetries => None,
+            AniRustError::FailedToFetchAfterRetries { last_error, .. } => last_error
+                .as_deref()
+                .map(|err| err as &(dyn StdError + 'static)),
+            AniRustError::AllDomainsFailed(errors) => errors
+                .last()
+                .map(|err| &err.error as &(dyn StdError + 'static)),
             AniRustError::ParseIntError(err) => Some(err),
-            AniRustError::NoDomainExists(_) => None,
-            AniRustError::UnknownError(_) => None,
+            AniRustError::NoProxiesAvailable
+            | AniRustError::HttpStatus { .. }
+            | AniRustError::NotFound { .. }
+            | AniRustError::AntiBotChallenge { .. }
+            | AniRustError::SelectorNotFound { .. }
+            | AniRustError::InvalidResponse { .. }
+            | AniRustError::IncompleteParse { .. }
+            | AniRustError::ExtractionFailed { .. }
+            | AniRustError::DecryptionKeyNotFound { .. }
+            | AniRustError::NoDomainExists(_)
+            | AniRustError::InvalidConfig(_)
+            | AniRustError::NoRecordedResponse(_)
+            | AniRustError::InvalidId { .. }
+            | AniRustError::UnknownError(_) => None,
         }
     }
 }
 
 impl AniRustError {
-    pub fn webhook_url(&self) -> String {
+    /// Whether the same call may succeed if it is tried again later.
+    ///
+    /// Network failures, rate limiting, server errors and anti-bot challenges
+    /// are retryable; missing pages, unexpected markup, bad configuration and invalid ids are not.
+    pub fn is_retryable(&self) -> bool {
         match self {
-            AniRustError::ReqwestError(_) => EnvVar::REQWEST_ERROR_WEBHOOK.get_config(),
-            AniRustError::HyperError(_) => EnvVar::REQWEST_ERROR_WEBHOOK.get_config(),
-            AniRustError::NoProxiesAvailable => {
-                EnvVar::NO_PROXIES_AVAILABLE_ERROR_WEBHOOK.get_config()
-            }
-            AniRustError::FailedToFetchAfterRetries => {
-                EnvVar::FAILED_TO_FETCH_AFTER_RETRIES_ERROR_WEBHOOK.get_config()
+            AniRustError::ReqwestError(err) => {
+                err.is_timeout()
+                    || err.is_connect()
+                    || err.is_request()
+                    || err.status().is_some_and(is_retryable_status)
             }
-            AniRustError::ParseIntError(_) | AniRustError::RegexError(_) => {
-                EnvVar::UTILS_ERROR_WEBHOOK.get_config()
+            AniRustError::NoProxiesAvailable
+            | AniRustError::FailedToFetchAfterRetries { .. }
+            | AniRustError::AntiBotChallenge { .. } => true,
+            AniRustError::AllDomainsFailed(errors) => {
+                errors.iter().any(|err| err.error.is_retryable())
             }
-            AniRustError::NoDomainExists(_) => String::new(),
-            AniRustError::UnknownError(_) => EnvVar::UNKNOWN_ERROR_WEBHOOK.get_config(),
+            AniRustError::HttpStatus { status, .. } => is_retryable_status(*status),
+            AniRustError::RegexError(_)
+            | AniRustError::NotFound { .. }
+            | AniRustError::SelectorNotFound { .. }
+            | AniRustError::InvalidResponse { .. }
+            | AniRustError::IncompleteParse { .. }
+            | AniRustError::ExtractionFailed { .. }
+            | AniRustError::DecryptionKeyNotFound { .. }
+            | AniRustError::ParseIntError(_)
+            | AniRustError::NoDomainExists(_)
+            | AniRustError::InvalidConfig(_)
+            | AniRustError::NoRecordedResponse(_)
+            | AniRustError::InvalidId { .. }
+            | AniRustError::UnknownError(_) => false,
         }
     }
-}
 
-impl Drop for AniRustError {
-    fn drop(&mut self) {
-        let webhook_url = self.webhook_url();
-        let error_message = format!("{}", self);
-        send_error_to_webhook(&webhook_url, &error_message);
+    /// Webhook from `webhooks` that should be notified about this error, if any
+    pub fn webhook_url<'a>(&self, webhooks: &'a Webhooks) -> Option<&'a Url> {
+        match self {
+            AniRustError::ReqwestError(_)
+            | AniRustError::HttpStatus { .. }
+            | AniRustError::NotFound { .. }
+            | AniRustError::AntiBotChallenge { .. } => webhooks.reqwest_error.as_ref(),
+            AniRustError::NoProxiesAvailable => webhooks.no_proxies_available.as_ref(),
+            AniRustError::FailedToFetchAfterRetries { .. } => {
+                webhooks.failed_to_fetch_after_retries.as_ref()
+            }
+            AniRustError::ParseIntError(_)
+            | AniRustError::RegexError(_)
+            | AniRustError::SelectorNotFound { .. }
+            | AniRustError::InvalidResponse { .. }
+            | AniRustError::IncompleteParse { .. } => webhooks.utils_error.as_ref(),
+            AniRustError::NoDomainExists(_)
+            | AniRustError::InvalidConfig(_)
+            | AniRustError::NoRecordedResponse(_)
+            | AniRustError::InvalidId { .. } => None,
+            AniRustError::AllDomainsFailed(_)
+            | AniRustError::ExtractionFailed { .. }
+            | AniRustError::DecryptionKeyNotFound { .. }
+            | AniRustError::UnknownError(_) => webhooks.unknown_error.as_ref(),
+        }
     }
 }
 
-fn send_error_to_webhook(webhook_url: &str, error_message: &str) {
-    if webhook_url.is_empty() {
-        return;
-    }
-
-    let webhook_url = webhook_url.to_string();
-    let error_message = error_message.to_string();
-
-    tokio::task::spawn_blocking(move || {
-        // Ensure the webhook URL is not empty
-        if webhook_url.is_empty() {
-            return;
-        }
-
-        let client = Client::new();
-        let now: DateTime<Utc> = Utc::now();
-        let timestamp = now.format("%Y-%m-%d %H:%M:%S").to_string();
-
-        let content = format!(
-            r#"{{"Timestamp": "{}", "Error": "{}"}}"#,
-            timestamp, error_message
-        );
-
-        let payload = json!({
-            "content": content,
-        });
-
-        // Perform the blocking HTTP request
-        let _res = client.post(&webhook_url).json(&payload).send();
-    });
+// 429 and 5xx responses are usually transient
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
 }
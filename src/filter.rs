@@ -0,0 +1,302 @@
+// filter.rs
+
+use chrono::{Datelike, Month, NaiveDate};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnimeType {
+    Movie = 1,
+    Tv = 2,
+    Ova = 3,
+    Ona = 4,
+    Special = 5,
+    Music = 6,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AiringStatus {
+    FinishedAiring = 1,
+    CurrentlyAiring = 2,
+    NotYetAired = 3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rating {
+    G = 1,
+    Pg = 2,
+    Pg13 = 3,
+    R = 4,
+    RPlus = 5,
+    Rx = 6,
+}
+
+/// Minimum MAL-style score, from 1 (appalling) to 10 (masterpiece)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Score {
+    Appalling = 1,
+    Horrible = 2,
+    VeryBad = 3,
+    Bad = 4,
+    Average = 5,
+    Fine = 6,
+    Good = 7,
+    VeryGood = 8,
+    Great = 9,
+    Masterpiece = 10,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Season {
+    Spring = 1,
+    Summer = 2,
+    Fall = 3,
+    Winter = 4,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    Sub = 1,
+    Dub = 2,
+    SubAndDub = 3,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SortOrder {
+    #[default]
+    Default,
+    RecentlyAdded,
+    RecentlyUpdated,
+    Score,
+    NameAz,
+    ReleasedDate,
+    MostWatched,
+}
+
+impl SortOrder {
+    fn as_str(self) -> &'static str {
+        match self {
+            SortOrder::Default => "default",
+            SortOrder::RecentlyAdded => "recently_added",
+            SortOrder::RecentlyUpdated => "recently_updated",
+            SortOrder::Score => "score",
+            SortOrder::NameAz => "name_az",
+            SortOrder::ReleasedDate => "released_date",
+            SortOrder::MostWatched => "most_watched",
+        }
+    }
+}
+
+/// Genres accepted by the filter, with the ids HiAnime uses for them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Genre {
+    Action = 1,
+    Adventure = 2,
+    Cars = 3,
+    Comedy = 4,
+    Dementia = 5,
+    Demons = 6,
+    Mystery = 7,
+    Drama = 8,
+    Ecchi = 9,
+    Fantasy = 10,
+    Game = 11,
+    Historical = 13,
+    Horror = 14,
+    Kids = 15,
+    Magic = 16,
+    MartialArts = 17,
+    Mecha = 18,
+    Music = 19,
+    Parody = 20,
+    Samurai = 21,
+    Romance = 22,
+    School = 23,
+    SciFi = 24,
+    Shoujo = 25,
+    ShoujoAi = 26,
+    Shounen = 27,
+    ShounenAi = 28,
+    Space = 29,
+    Sports = 30,
+    SuperPower = 31,
+    Vampire = 32,
+    Harem = 35,
+    SliceOfLife = 36,
+    Supernatural = 37,
+    Military = 38,
+    Police = 39,
+    Psychological = 40,
+    Thriller = 41,
+    Seinen = 42,
+    Josei = 43,
+    Isekai = 44,
+}
+
+/// Start or end of the airing period; HiAnime accepts a year, a month or a day
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FilterDate {
+    Year(i32),
+    Month(i32, Month),
+    Day(NaiveDate),
+}
+
+impl FilterDate {
+    fn year_month_day(self) -> (i32, Option<u32>, Option<u32>) {
+        match self {
+            FilterDate::Year(year) => (year, None, None),
+            FilterDate::Month(year, month) => (year, Some(month.number_from_month()), None),
+            FilterDate::Day(date) => (date.year(), Some(date.month()), Some(date.day())),
+        }
+    }
+}
+
+impl From<NaiveDate> for FilterDate {
+    fn from(date: NaiveDate) -> Self {
+        FilterDate::Day(date)
+    }
+}
+
+/// Query of the `/filter` page; every facet left unset matches everything.
+///
+/// ```
+/// use aniscraper::filter::{AnimeType, Genre, SearchFilter, SortOrder};
+///
+/// let filter = SearchFilter::new()
+///     .anime_type(AnimeType::Tv)
+///     .genre(Genre::Action)
+///     .genre(Genre::Fantasy)
+///     .sort(SortOrder::Score);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchFilter {
+    keyword: Option<String>,
+    anime_type: Option<AnimeType>,
+    status: Option<AiringStatus>,
+    rating: Option<Rating>,
+    score: Option<Score>,
+    season: Option<Season>,
+    language: Option<Language>,
+    start_date: Option<FilterDate>,
+    end_date: Option<FilterDate>,
+    sort: SortOrder,
+    genres: Vec<Genre>,
+}
+
+impl SearchFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn keyword(mut self, keyword: &str) -> Self {
+        self.keyword = Some(keyword.to_string());
+        self
+    }
+
+    pub fn anime_type(mut self, anime_type: AnimeType) -> Self {
+        self.anime_type = Some(anime_type);
+        self
+    }
+
+    pub fn status(mut self, status: AiringStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn rating(mut self, rating: Rating) -> Self {
+        self.rating = Some(rating);
+        self
+    }
+
+    pub fn score(mut self, score: Score) -> Self {
+        self.score = Some(score);
+        self
+    }
+
+    pub fn season(mut self, season: Season) -> Self {
+        self.season = Some(season);
+        self
+    }
+
+    pub fn language(mut self, language: Language) -> Self {
+        self.language = Some(language);
+        self
+    }
+
+    pub fn start_date(mut self, date: impl Into<FilterDate>) -> Self {
+        self.start_date = Some(date.into());
+        self
+    }
+
+    pub fn end_date(mut self, date: impl Into<FilterDate>) -> Self {
+        self.end_date = Some(date.into());
+        self
+    }
+
+    pub fn sort(mut self, sort: SortOrder) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// Adds `genre`; results must have every genre added
+    pub fn genre(mut self, genre: Genre) -> Self {
+        if !self.genres.contains(&genre) {
+            self.genres.push(genre);
+        }
+        self
+    }
+
+    pub fn genres(self, genres: impl IntoIterator<Item = Genre>) -> Self {
+        genres.into_iter().fold(self, SearchFilter::genre)
+    }
+
+    /// Query parameters of the filter, without the page number.
+    pub fn query_pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = vec![];
+
+        if let Some(keyword) = &self.keyword {
+            pairs.push(("keyword", keyword.clone()));
+        }
+        let codes = [
+            ("type", self.anime_type.map(|v| v as u8)),
+            ("status", self.status.map(|v| v as u8)),
+            ("rated", self.rating.map(|v| v as u8)),
+            ("score", self.score.map(|v| v as u8)),
+            ("season", self.season.map(|v| v as u8)),
+            ("language", self.language.map(|v| v as u8)),
+        ];
+        for (name, code) in codes {
+            if let Some(code) = code {
+                pairs.push((name, code.to_string()));
+            }
+        }
+
+        for (keys, date) in [
+            (["sy", "sm", "sd"], self.start_date),
+            (["ey", "em", "ed"], self.end_date),
+        ] {
+            if let Some(date) = date {
+                let (year, month, day) = date.year_month_day();
+                pairs.push((keys[0], year.to_string()));
+                if let Some(month) = month {
+                    pairs.push((keys[1], month.to_string()));
+                }
+                if let Some(day) = day {
+                    pairs.push((keys[2], day.to_string()));
+                }
+            }
+        }
+
+        if self.sort != SortOrder::Default {
+            pairs.push(("sort", self.sort.as_str().to_string()));
+        }
+        if !self.genres.is_empty() {
+            let ids: Vec<String> = self
+                .genres
+                .iter()
+                .map(|genre| (*genre as u8).to_string())
+                .collect();
+            pairs.push(("genres", ids.join(",")));
+        }
+
+        pairs
+    }
+}
@@ -1,59 +1,39 @@
-use lazy_static::lazy_static;
-use regex::Regex;
-use scraper::{selectable::Selectable, Html, Selector};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
+use reqwest::Url;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use tokio::task::JoinHandle;
 
 use crate::{
-    env::{self, EnvVar, SecretConfig},
-    error::AniRustError,
-    proxy::{load_proxies, Proxy},
+    cache::{CacheKind, ResponseCache},
+    config::{ClientConfig, HiAnimeRustBuilder},
+    env::SecretConfig,
+    error::{AniRustError, DomainError},
+    filter::SearchFilter,
+    parse::{
+        parse_about_anime, parse_anime_qtip, parse_atoz, parse_category, parse_characters,
+        parse_episodes, parse_home, parse_next_episode, parse_schedule, parse_search,
+        parse_search_suggestions, parse_servers, ParseMode, Parsed,
+    },
+    proxy::ProxyPool,
+    rate_limit::RateLimiter,
+    reporter::{ErrorReporter, WebhookReporter},
+    retry::RetryPolicy,
+    selectors::{SelectorProfile, Selectors},
     servers::{AnimeServer, EpisodeType, MegaCloudServer, ServerExtractedInfo, StreamTapeServer},
-    utils::{anirust_error_vec_to_string, get_ajax_curl, get_curl},
+    transport::{Fetcher, ReqwestFetcher, Transport},
+    utils::{get_ajax_curl, get_ajax_html, get_curl},
 };
 
-lazy_static! {
-    static ref TRENDING_SELECTOR: Selector =
-        Selector::parse("#anime-trending #trending-home .swiper-wrapper .swiper-slide").unwrap();
-    static ref LATEST_EPISODES_SELECTOR: Selector = Selector::parse(
-        "#main-content .block_area_home:nth-of-type(1) .tab-content .film_list-wrap .flw-item"
-    )
-    .unwrap();
-    static ref TOP_UPCOMING_SELECTOR: Selector = Selector::parse(
-        "#main-content .block_area_home:nth-of-type(3) .tab-content .film_list-wrap .flw-item"
-    )
-    .unwrap();
-    static ref GENRES_SELECTOR: Selector = Selector::parse(
-        "#main-sidebar .block_area.block_area_sidebar.block_area-genres .sb-genre-list li"
-    )
-    .unwrap();
-    static ref SPOTLIGHT_SELECTOR: Selector =
-        Selector::parse("#slider .swiper-wrapper .swiper-slide").unwrap();
-    static ref FEATURED_SELECTOR: Selector =
-        Selector::parse("#anime-featured .row div:nth-of-type(1) .anif-block-ul ul li").unwrap();
-    static ref TOP_10_SELECTOR: Selector =
-        Selector::parse("#main-sidebar .block_area-realtime [id^=\"top-viewed-\"]").unwrap();
-    static ref A_TO_Z_SELECTOR: Selector = Selector::parse("#main-wrapper div div.page-az-wrap section div.tab-content div div.film_list-wrap .flw-item").unwrap();
-    static ref NAVIGATION_SELECTOR: Selector = Selector::parse("div.pre-pagination.mt-5.mb-5 > nav > ul > li:last-child a").unwrap();
-    static ref ABOUT_ANIME_SELECTOR: Selector = Selector::parse("#ani_detail .ani_detail-stage .container .anis-content").unwrap();
-    static ref MOST_POPULAR_ANIME_SELECTOR: Selector = Selector::parse("#main-sidebar .block_area.block_area_sidebar.block_area-realtime:nth-of-type(2) .anif-block-ul ul li").unwrap();
-    static ref RELATED_ANIME_SELECTOR: Selector = Selector::parse("#main-sidebar .block_area.block_area_sidebar.block_area-realtime:nth-of-type(1) .anif-block-ul ul li").unwrap();
-    static ref RECOMMENDED_ANIME_SELECTOR: Selector = Selector::parse("#main-content .block_area.block_area_category .tab-content .flw-item").unwrap();
-    static ref SEASONS_SELECTOR: Selector = Selector::parse(".os-list a.os-item").unwrap();
-    static ref EPISODE_SELECTOR: Selector = Selector::parse(".detail-infor-content .ss-list a").unwrap();
-    static ref CATEGORY_SELECTOR: Selector = Selector::parse("#main-content .tab-content .film_list-wrap .flw-item").unwrap();
-    static ref SEARCH_SELECTOR: Selector = Selector::parse("#main-content .tab-content .film_list-wrap .flw-item").unwrap();
-    static ref EPISODE_NO_SELECTOR: Selector = Selector::parse(".server-notice strong").unwrap();
-    static ref EPISODE_SUB_SELECTOR: Selector = Selector::parse(".ps_-block.ps_-block-sub.servers-sub .ps__-list .server-item").unwrap();
-    static ref EPISODE_DUB_SELECTOR: Selector = Selector::parse(".ps_-block.ps_-block-sub.servers-dub .ps__-list .server-item").unwrap();
-    static ref EPISODE_RAW_SELECTOR: Selector = Selector::parse(".ps_-block.ps_-block-sub.servers-raw .ps__-list .server-item").unwrap();
-}
-
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct HiAnimeRust {
     domains: Vec<String>,
-    proxies: Vec<Proxy>,
-    secret: Option<SecretConfig>,
+    transport: Transport,
+    reporter: Arc<dyn ErrorReporter>,
+    cache: Option<Arc<ResponseCache>>,
+    bypass_cache: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -87,6 +67,27 @@ pub struct SearchInfo {
     pub genres: Vec<String>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SearchSuggestion {
+    pub id: String,
+    pub title: String,
+    pub japanese: String,
+    pub image: String,
+    pub aired: String,
+    pub category: String,
+    pub duration: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScheduledAnime {
+    pub id: String,
+    pub title: String,
+    pub japanese: String,
+    /// Air time in the offset the schedule was requested for
+    pub airing_at: DateTime<FixedOffset>,
+    pub episode_no: u32,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct EpisodesInfo {
     pub total_episodes: u32,
@@ -219,6 +220,51 @@ pub struct AboutAnime {
     pub related_animes: Vec<SideBarAnimes>,
     pub recommended_animes: Vec<Anime>,
     pub seasons: Vec<AnimeSeason>,
+    pub characters: Vec<Character>,
+}
+
+/// Short summary shown when hovering an anime card.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AnimeQtip {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    pub mal_score: String,
+    pub quality: String,
+    pub category: String,
+    pub subs: u32,
+    pub dubs: u32,
+    pub eps: u32,
+    pub japanese: String,
+    pub synonyms: String,
+    pub aired: String,
+    pub status: String,
+    pub genres: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Character {
+    pub id: String,
+    pub name: String,
+    pub role: String,
+    pub image: String,
+    pub voice_actors: Vec<VoiceActor>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VoiceActor {
+    pub id: String,
+    pub name: String,
+    pub language: String,
+    pub image: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CharactersInfo {
+    pub total_pages: u32,
+    pub current_page: u32,
+    pub has_next_page: bool,
+    pub characters: Vec<Character>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -236,164 +282,547 @@ pub struct ServerInfo {
     pub raw: Vec<Server>,
 }
 
-trait HasClass {
-    fn has_class(&self, class_name: &str) -> bool;
+// Body returned by `get_page` and friends, with the cache kind it still has to be
+// stored under once parsed; `None` when it was served from the cache
+struct Fetched {
+    body: String,
+    pending: Option<CacheKind>,
 }
 
-impl HasClass for scraper::ElementRef<'_> {
-    fn has_class(&self, class_name: &str) -> bool {
-        self.value()
-            .attr("class")
-            .map(|class| class.split_whitespace().any(|c| c == class_name))
-            .unwrap_or(false)
+impl Fetched {
+    fn cached(body: String) -> Self {
+        Fetched {
+            body,
+            pending: None,
+        }
+    }
+
+    fn fresh(kind: CacheKind, body: String) -> Self {
+        Fetched {
+            body,
+            pending: Some(kind),
+        }
     }
 }
 
 impl HiAnimeRust {
+    /// Builds a client from a legacy [`SecretConfig`], or from the environment when `None`.
+    ///
+    /// Invalid settings are reported and replaced by the defaults; use
+    /// [`HiAnimeRust::builder`] to get them as an error instead.
     pub async fn new(secret: Option<SecretConfig>) -> Self {
-        let secret_clone = initialize_secret(secret);
-        let domain_list = EnvVar::HIANIME_DOMAINS.get_config();
-        let domains: Vec<String> = if domain_list.is_empty() {
-            vec!["https://aniwatchtv.to".to_string()]
-        } else {
-            domain_list
-                .split(',')
-                .map(|s| s.trim().to_string())
-                .collect()
+        Self::with_fetcher(secret, Arc::new(ReqwestFetcher::new())).await
+    }
+
+    /// Same as [`HiAnimeRust::new`], but sends every request through `fetcher`.
+    pub async fn with_fetcher(secret: Option<SecretConfig>, fetcher: Arc<dyn Fetcher>) -> Self {
+        let builder = match secret {
+            Some(secret) => HiAnimeRustBuilder::from_secret(secret),
+            None => Ok(HiAnimeRustBuilder::new()),
         };
 
-        let proxies = match load_proxies().await {
-            Ok(p) => p,
-            Err(e) => {
-                eprintln!("Failed to load proxies: {:?}", e);
-                Vec::new()
-            }
+        let (config, invalid) = match builder.and_then(|builder| builder.build_config()) {
+            Ok(config) => (config, None),
+            Err(e) => (ClientConfig::default(), Some(e)),
         };
 
-        HiAnimeRust {
+        let reporter = Arc::new(WebhookReporter::new(config.webhooks.clone()));
+        if let Some(e) = invalid {
+            reporter.report(&e);
+        }
+        Self::from_config(config, fetcher, reporter, None).await
+    }
+
+    /// Typed builder, see [`HiAnimeRustBuilder`].
+    pub fn builder() -> HiAnimeRustBuilder {
+        HiAnimeRustBuilder::new()
+    }
+
+    pub(crate) async fn from_config(
+        config: ClientConfig,
+        fetcher: Arc<dyn Fetcher>,
+        reporter: Arc<dyn ErrorReporter>,
+        cache: Option<Arc<ResponseCache>>,
+    ) -> Self {
+        let domains = config
+            .domains
+            .iter()
+            .map(|domain| domain.as_str().trim_end_matches('/').to_string())
+            .collect();
+
+        let proxies =
+            ProxyPool::from_sources(config.proxy_sources.clone(), config.proxy_pool.clone());
+        if let Err(e) = proxies.refresh().await {
+            reporter.report(&e);
+        }
+
+        let refresh_interval = config.proxy_refresh_interval;
+        let probe_url = config.proxy_probe_url.clone();
+
+        let hianime = HiAnimeRust {
             domains,
-            proxies,
-            secret: secret_clone,
+            transport: Transport::new(
+                fetcher,
+                Arc::new(proxies),
+                Arc::new(RateLimiter::new(config.rate_limit.clone())),
+                Arc::new(config),
+            ),
+            reporter,
+            cache,
+            bypass_cache: false,
+        };
+
+        if let Some(interval) = refresh_interval {
+            hianime.spawn_proxy_refresh(interval);
+        }
+        if let Some(probe_url) = probe_url {
+            hianime.spawn_proxy_validation(probe_url.as_str());
+        }
+
+        hianime
+    }
+
+    /// Resolved settings of this client.
+    pub fn config(&self) -> &ClientConfig {
+        self.transport.config()
+    }
+
+    /// Transport shared by every request of this client, usable with the `servers` extractors.
+    pub fn transport(&self) -> &Transport {
+        &self.transport
+    }
+
+    /// Reloads the proxy lists and swaps them in without disturbing running requests.
+    ///
+    /// Returns the number of proxies in the pool afterwards.
+    pub async fn refresh_proxies(&self) -> Result<usize, AniRustError> {
+        self.transport
+            .proxies()
+            .refresh()
+            .await
+            .map_err(|e| self.report(e))
+    }
+
+    /// Refreshes the proxy lists every `interval` in the background.
+    ///
+    /// The task stops on its own once this client is dropped.
+    pub fn spawn_proxy_refresh(&self, interval: Duration) -> JoinHandle<()> {
+        let proxies = Arc::downgrade(self.transport.proxies());
+        let reporter = self.reporter.clone();
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            // The first tick completes immediately
+            ticker.tick().await;
+
+            loop {
+                ticker.tick().await;
+                let Some(proxies) = proxies.upgrade() else {
+                    break;
+                };
+                if let Err(e) = proxies.refresh().await {
+                    reporter.report(&e);
+                }
+            }
+        })
+    }
+
+    /// Client sharing this one's proxies and settings, but retrying with `policy`.
+    ///
+    /// Use it to override the policy for a single call, e.g.
+    /// `hianime.with_retry_policy(RetryPolicy::no_retries()).scrape_home().await`.
+    pub fn with_retry_policy(&self, policy: RetryPolicy) -> Self {
+        HiAnimeRust {
+            domains: self.domains.clone(),
+            transport: self.transport.with_retry_policy(policy),
+            ..self.clone()
+        }
+    }
+
+    /// Client that parses pages with `profile` instead of the configured selectors.
+    ///
+    /// Lets a broken selector be patched at runtime without rebuilding the client.
+    pub fn with_selector_profile(&self, profile: SelectorProfile) -> Result<Self, AniRustError> {
+        let mut config = ClientConfig::clone(self.config());
+        config.selectors = Arc::new(Selectors::new(profile)?);
+
+        Ok(HiAnimeRust {
+            domains: self.domains.clone(),
+            transport: self.transport.with_config(config),
+            ..self.clone()
+        })
+    }
+
+    /// Client that skips cache lookups but still stores fresh responses.
+    ///
+    /// Use it to force a refetch for a single call, e.g.
+    /// `hianime.bypass_cache().scrape_home().await`.
+    pub fn bypass_cache(&self) -> Self {
+        HiAnimeRust {
+            bypass_cache: true,
+            ..self.clone()
+        }
+    }
+
+    /// Response cache of this client, if one was configured.
+    pub fn cache(&self) -> Option<&ResponseCache> {
+        self.cache.as_deref()
+    }
+
+    /// Proxy pool used by this client, along with the health of each proxy.
+    pub fn proxy_pool(&self) -> &ProxyPool {
+        self.transport.proxies()
+    }
+
+    /// Probes every proxy against `probe_url` and quarantines the dead ones.
+    ///
+    /// Returns the number of healthy proxies.
+    pub async fn validate_proxies(&self, probe_url: &str) -> usize {
+        self.transport
+            .proxies()
+            .validate(
+                self.transport.fetcher().clone(),
+                probe_url,
+                self.transport.config().retry_policy.attempt_timeout,
+            )
+            .await
+    }
+
+    /// Runs [`HiAnimeRust::validate_proxies`] in the background.
+    pub fn spawn_proxy_validation(&self, probe_url: &str) -> JoinHandle<usize> {
+        let proxies = self.transport.proxies().clone();
+        let fetcher = self.transport.fetcher().clone();
+        let timeout = self.transport.config().retry_policy.attempt_timeout;
+        let probe_url = probe_url.to_string();
+
+        tokio::spawn(async move { proxies.validate(fetcher, &probe_url, timeout).await })
+    }
+
+    // Fetches a page through the response cache, unless it is bypassed
+    async fn get_page(&self, kind: CacheKind, url: &str) -> Result<Fetched, AniRustError> {
+        if let Some(body) = self.cached(kind, url).await {
+            return Ok(Fetched::cached(body));
+        }
+        let body = get_curl(&self.transport, url).await?;
+        Ok(Fetched::fresh(kind, body))
+    }
+
+    // Same as `get_page` for AJAX endpoints; the extracted field is cached
+    async fn get_ajax(
+        &self,
+        kind: CacheKind,
+        url: &str,
+        field: &str,
+    ) -> Result<Fetched, AniRustError> {
+        if let Some(data) = self.cached(kind, url).await {
+            return Ok(Fetched::cached(data));
+        }
+        let data = get_ajax_curl(&self.transport, url, field).await?;
+        Ok(Fetched::fresh(kind, data))
+    }
+
+    // Same as `get_page` for AJAX endpoints that answer with HTML
+    async fn get_ajax_page(&self, kind: CacheKind, url: &str) -> Result<Fetched, AniRustError> {
+        if let Some(body) = self.cached(kind, url).await {
+            return Ok(Fetched::cached(body));
         }
+        let body = get_ajax_html(&self.transport, url).await?;
+        Ok(Fetched::fresh(kind, body))
+    }
+
+    async fn cached(&self, kind: CacheKind, url: &str) -> Option<String> {
+        match &self.cache {
+            Some(cache) if !self.bypass_cache => cache.get(kind, url).await,
+            _ => None,
+        }
+    }
+
+    async fn store(&self, kind: CacheKind, url: &str, body: &str) {
+        if let Some(cache) = &self.cache {
+            cache.put(kind, url, body.to_string()).await;
+        }
+    }
+
+    // Hands an error that is about to leave the public API to the reporter
+    fn report(&self, error: AniRustError) -> AniRustError {
+        self.reporter.report(&error);
+        error
+    }
+
+    // Applies the configured `ParseMode` to a page parsed from `url`, then caches
+    // `body` under `pending` when it was freshly fetched and the check passed
+    async fn checked<T>(
+        &self,
+        pending: Option<CacheKind>,
+        url: &str,
+        body: &str,
+        parsed: Parsed<T>,
+    ) -> Result<T, AniRustError> {
+        if !parsed.diagnostics.is_clean() {
+            let error = AniRustError::IncompleteParse {
+                url: url.to_string(),
+                diagnostics: parsed.diagnostics,
+            };
+            match self.config().parse_mode {
+                ParseMode::Lenient => {}
+                ParseMode::Report => self.reporter.report(&error),
+                ParseMode::Strict => return Err(self.report(error)),
+            }
+        }
+
+        if let Some(kind) = pending {
+            self.store(kind, url, body).await;
+        }
+        Ok(parsed.data)
     }
 
     pub async fn scrape_home(&self) -> Result<HomeInfo, AniRustError> {
-        let mut error_vec = vec![];
+        let mut errors = vec![];
         let mut curl = String::new();
+        let mut page_url = String::new();
+        let mut pending = None;
 
         for domain in &self.domains {
-            let url = format!("{}/home", domain);
+            let url = build_url(domain, &["home"], &[]);
 
-            match get_curl(&url, &self.proxies).await {
-                Ok(curl_string) => {
-                    curl = curl_string;
+            match self.get_page(CacheKind::Home, &url).await {
+                Ok(fetched) => {
+                    curl = fetched.body;
+                    pending = fetched.pending;
+                    page_url = url;
                     break;
                 }
-                Err(e) => {
-                    error_vec.push(Some(e));
-                }
+                // Mirrors share their catalogue, so the other domains would 404 too
+                Err(error @ AniRustError::NotFound { .. }) => return Err(self.report(error)),
+                Err(error) => errors.push(DomainError {
+                    domain: domain.clone(),
+                    error,
+                }),
             }
         }
 
         if curl.is_empty() {
-            let error_string: String = anirust_error_vec_to_string(error_vec);
-            return Err(AniRustError::UnknownError(error_string));
+            return Err(self.report(AniRustError::AllDomainsFailed(errors)));
         }
 
-        let document = Html::parse_document(&curl);
-
-        let trending = extract_minimal_anime(&document, &TRENDING_SELECTOR);
-        let latest_episodes = extract_anime_data(&document, &LATEST_EPISODES_SELECTOR);
-        let top_upcoming_animes = extract_anime_data(&document, &TOP_UPCOMING_SELECTOR);
-        let spotlight_animes = extract_spotlight_anime_data(&document, &SPOTLIGHT_SELECTOR);
-        let genres = extract_genres(&document, &GENRES_SELECTOR);
-        let top_10_animes = extract_top_10(&document, &TOP_10_SELECTOR);
-
-        let (top_airing_animes, most_popular_animes, most_favorite_animes, latest_completed_animes) =
-            extract_featured_anime(&document, &FEATURED_SELECTOR);
-        let featured = FeaturedAnime {
-            top_airing_animes,
-            most_popular_animes,
-            most_favorite_animes,
-            latest_completed_animes,
-        };
-
-        Ok(HomeInfo {
-            trending,
-            latest_episodes,
-            top_upcoming_animes,
-            spotlight_animes,
-            featured,
-            top_10_animes,
-            genres,
-        })
+        self.checked(
+            pending,
+            &page_url,
+            &curl,
+            parse_home(&self.config().selectors, &curl),
+        )
+        .await
     }
 
     pub async fn scrape_atoz(&self, page_no: u32) -> Result<AtoZ, AniRustError> {
-        let mut error_vec = vec![];
+        let mut errors = vec![];
         let mut curl = String::new();
+        let mut page_url = String::new();
+        let mut pending = None;
 
         for domain in &self.domains {
-            let url = format!("{}/az-list?page={}", domain, page_no);
+            let url = build_url(domain, &["az-list"], &[("page", &page_no.to_string())]);
 
-            match get_curl(&url, &self.proxies).await {
-                Ok(curl_string) => {
-                    curl = curl_string;
+            match self.get_page(CacheKind::AtoZ, &url).await {
+                Ok(fetched) => {
+                    curl = fetched.body;
+                    pending = fetched.pending;
+                    page_url = url;
                     break;
                 }
-                Err(e) => {
-                    error_vec.push(Some(e));
-                }
+                Err(error @ AniRustError::NotFound { .. }) => return Err(self.report(error)),
+                Err(error) => errors.push(DomainError {
+                    domain: domain.clone(),
+                    error,
+                }),
             }
         }
 
         if curl.is_empty() {
-            let error_string: String = anirust_error_vec_to_string(error_vec);
-            return Err(AniRustError::UnknownError(error_string));
+            return Err(self.report(AniRustError::AllDomainsFailed(errors)));
         }
 
-        let document = Html::parse_document(&curl);
+        self.checked(
+            pending,
+            &page_url,
+            &curl,
+            parse_atoz(&self.config().selectors, &curl, page_no),
+        )
+        .await
+    }
 
-        let animes = extract_anime_data(&document, &A_TO_Z_SELECTOR);
+    pub async fn scrape_about_anime(&self, id: &str) -> Result<AboutAnime, AniRustError> {
+        validate_id(id).map_err(|e| self.report(e))?;
+        let mut errors = vec![];
+        let mut curl = String::new();
+        let mut page_url = String::new();
+        let mut pending = None;
 
-        let total_pages = get_last_page_no(&document);
-        let current_page = page_no;
-        let has_next_page = page_no != total_pages;
+        for domain in &self.domains {
+            let url = build_url(domain, &[id], &[]);
 
-        Ok(AtoZ {
-            has_next_page,
-            current_page,
-            total_pages,
-            animes,
-        })
+            match self.get_page(CacheKind::About, &url).await {
+                Ok(fetched) => {
+                    curl = fetched.body;
+                    pending = fetched.pending;
+                    page_url = url;
+                    break;
+                }
+                Err(error @ AniRustError::NotFound { .. }) => return Err(self.report(error)),
+                Err(error) => errors.push(DomainError {
+                    domain: domain.clone(),
+                    error,
+                }),
+            }
+        }
+
+        if curl.is_empty() {
+            return Err(self.report(AniRustError::AllDomainsFailed(errors)));
+        }
+
+        let Some(parsed) = parse_about_anime(&self.config().selectors, &curl) else {
+            return Err(self.report(AniRustError::SelectorNotFound {
+                url: page_url,
+                selector: self.config().selectors.profile().about.clone(),
+            }));
+        };
+        let about = self.checked(pending, &page_url, &curl, parsed).await?;
+        if let (Some(cache), Some(anime_id)) = (&self.cache, id.split('-').next_back()) {
+            cache.set_finished(anime_id, about.status == "Finished Airing");
+        }
+        Ok(about)
     }
 
-    pub async fn scrape_about_anime(&self, id: &str) -> Result<AboutAnime, AniRustError> {
-        let mut error_vec = vec![];
+    /// Hover card of the anime with the numeric `anime_id`, as found at the end of
+    /// its id or in [`AboutAnime::anime_id`]; much lighter than [`scrape_about_anime`](Self::scrape_about_anime).
+    pub async fn scrape_anime_qtip(&self, anime_id: u32) -> Result<AnimeQtip, AniRustError> {
+        let mut errors = vec![];
         let mut curl = String::new();
+        let mut page_url = String::new();
+        let mut pending = None;
+        let anime_id = anime_id.to_string();
 
         for domain in &self.domains {
-            let url = format!("{}/{}", domain, id);
+            let url = build_url(domain, &["ajax", "movie", "qtip", &anime_id], &[]);
 
-            match get_curl(&url, &self.proxies).await {
-                Ok(curl_string) => {
-                    curl = curl_string;
+            match self.get_ajax_page(CacheKind::About, &url).await {
+                Ok(fetched) => {
+                    curl = fetched.body;
+                    pending = fetched.pending;
+                    page_url = url;
+                    break;
+                }
+                Err(error @ AniRustError::NotFound { .. }) => return Err(self.report(error)),
+                Err(error) => errors.push(DomainError {
+                    domain: domain.clone(),
+                    error,
+                }),
+            }
+        }
+
+        if curl.is_empty() {
+            return Err(self.report(AniRustError::AllDomainsFailed(errors)));
+        }
+
+        self.checked(
+            pending,
+            &page_url,
+            &curl,
+            parse_anime_qtip(&self.config().selectors, &curl),
+        )
+        .await
+    }
+
+    /// Page `page_no` of the full cast of `id`; the about page only lists the main characters.
+    pub async fn scrape_characters(
+        &self,
+        id: &str,
+        page_no: u32,
+    ) -> Result<CharactersInfo, AniRustError> {
+        validate_id(id).map_err(|e| self.report(e))?;
+        let mut errors = vec![];
+        // Anime without a cast get an empty list, so an empty body is a success
+        let mut curl = None;
+        let mut page_url = String::new();
+        let mut pending = None;
+        let anime_id = id.split('-').next_back().unwrap_or_default();
+
+        for domain in &self.domains {
+            let url = build_url(
+                domain,
+                &["ajax", "character", "list", anime_id],
+                &[("page", &page_no.to_string())],
+            );
+
+            match self.get_ajax(CacheKind::About, &url, "html").await {
+                Ok(fetched) => {
+                    curl = Some(fetched.body);
+                    pending = fetched.pending;
+                    page_url = url;
                     break;
                 }
-                Err(e) => {
-                    error_vec.push(Some(e));
+                Err(error @ AniRustError::NotFound { .. }) => return Err(self.report(error)),
+                Err(error) => errors.push(DomainError {
+                    domain: domain.clone(),
+                    error,
+                }),
+            }
+        }
+
+        let Some(curl) = curl else {
+            return Err(self.report(AniRustError::AllDomainsFailed(errors)));
+        };
+
+        self.checked(
+            pending,
+            &page_url,
+            &curl,
+            parse_characters(&self.config().selectors, &curl, page_no),
+        )
+        .await
+    }
+
+    /// When the next episode of `id` airs, or `None` when the watch page shows
+    /// no countdown, as for finished or unscheduled shows.
+    pub async fn scrape_next_episode_schedule(
+        &self,
+        id: &str,
+    ) -> Result<Option<DateTime<Utc>>, AniRustError> {
+        validate_id(id).map_err(|e| self.report(e))?;
+        let mut errors = vec![];
+        let mut curl = String::new();
+        let mut page_url = String::new();
+        let mut pending = None;
+
+        for domain in &self.domains {
+            let url = build_url(domain, &["watch", id], &[]);
+
+            match self.get_page(CacheKind::Schedule, &url).await {
+                Ok(fetched) => {
+                    curl = fetched.body;
+                    pending = fetched.pending;
+                    page_url = url;
+                    break;
                 }
+                Err(error @ AniRustError::NotFound { .. }) => return Err(self.report(error)),
+                Err(error) => errors.push(DomainError {
+                    domain: domain.clone(),
+                    error,
+                }),
             }
         }
 
         if curl.is_empty() {
-            let error_string: String = anirust_error_vec_to_string(error_vec);
-            return Err(AniRustError::UnknownError(error_string));
+            return Err(self.report(AniRustError::AllDomainsFailed(errors)));
         }
 
-        let document = Html::parse_document(&curl);
-        let about = extract_anime_about_info(&document, &ABOUT_ANIME_SELECTOR);
-        Ok(about)
+        self.checked(
+            pending,
+            &page_url,
+            &curl,
+            parse_next_episode(&self.config().selectors, &curl),
+        )
+        .await
     }
 
     pub async fn scrape_category(
@@ -401,44 +830,45 @@ impl HiAnimeRust {
         category: &str,
         page_no: u32,
     ) -> Result<CategoryInfo, AniRustError> {
-        let mut error_vec = vec![];
+        // Categories such as `genre/action` span several path segments
+        let segments: Vec<&str> = category.trim_matches('/').split('/').collect();
+        for segment in &segments {
+            validate_id(segment).map_err(|e| self.report(e))?;
+        }
+        let mut errors = vec![];
         let mut curl = String::new();
+        let mut page_url = String::new();
+        let mut pending = None;
 
         for domain in &self.domains {
-            let url = format!("{}/{}?page={}", domain, category, page_no);
+            let url = build_url(domain, &segments, &[("page", &page_no.to_string())]);
 
-            match get_curl(&url, &self.proxies).await {
-                Ok(curl_string) => {
-                    curl = curl_string;
+            match self.get_page(CacheKind::Category, &url).await {
+                Ok(fetched) => {
+                    curl = fetched.body;
+                    pending = fetched.pending;
+                    page_url = url;
                     break;
                 }
-                Err(e) => {
-                    error_vec.push(Some(e));
-                }
+                Err(error @ AniRustError::NotFound { .. }) => return Err(self.report(error)),
+                Err(error) => errors.push(DomainError {
+                    domain: domain.clone(),
+                    error,
+                }),
             }
         }
 
         if curl.is_empty() {
-            let error_string: String = anirust_error_vec_to_string(error_vec);
-            return Err(AniRustError::UnknownError(error_string));
+            return Err(self.report(AniRustError::AllDomainsFailed(errors)));
         }
 
-        let document = Html::parse_document(&curl);
-        let animes = extract_anime_data(&document, &CATEGORY_SELECTOR);
-        let top_10_animes = extract_top_10(&document, &TOP_10_SELECTOR);
-        let genres = extract_genres(&document, &GENRES_SELECTOR);
-        let total_pages = get_last_page_no(&document);
-        let current_page = page_no;
-        let has_next_page = page_no != total_pages;
-
-        Ok(CategoryInfo {
-            total_pages,
-            current_page,
-            has_next_page,
-            animes,
-            top_10_animes,
-            genres,
-        })
+        self.checked(
+            pending,
+            &page_url,
+            &curl,
+            parse_category(&self.config().selectors, &curl, page_no),
+        )
+        .await
     }
 
     pub async fn scrape_search(
@@ -446,133 +876,282 @@ impl HiAnimeRust {
         query: &str,
         page_no: u32,
     ) -> Result<SearchInfo, AniRustError> {
-        let mut error_vec = vec![];
+        let mut errors = vec![];
         let mut curl = String::new();
+        let mut page_url = String::new();
+        let mut pending = None;
 
         for domain in &self.domains {
-            let url = format!("{}/search?keyword={}&page={}", domain, query, page_no);
+            let url = build_url(
+                domain,
+                &["search"],
+                &[("keyword", query), ("page", &page_no.to_string())],
+            );
 
-            match get_curl(&url, &self.proxies).await {
-                Ok(curl_string) => {
-                    curl = curl_string;
+            match self.get_page(CacheKind::Search, &url).await {
+                Ok(fetched) => {
+                    curl = fetched.body;
+                    pending = fetched.pending;
+                    page_url = url;
                     break;
                 }
-                Err(e) => {
-                    error_vec.push(Some(e));
-                }
+                Err(error @ AniRustError::NotFound { .. }) => return Err(self.report(error)),
+                Err(error) => errors.push(DomainError {
+                    domain: domain.clone(),
+                    error,
+                }),
             }
         }
 
         if curl.is_empty() {
-            let error_string: String = anirust_error_vec_to_string(error_vec);
-            return Err(AniRustError::UnknownError(error_string));
+            return Err(self.report(AniRustError::AllDomainsFailed(errors)));
         }
 
-        let document = Html::parse_document(&curl);
-        let most_popular_selector = Selector::parse(
-            "#main-sidebar .block_area.block_area_sidebar.block_area-realtime .anif-block-ul ul li",
+        self.checked(
+            pending,
+            &page_url,
+            &curl,
+            parse_search(&self.config().selectors, &curl, page_no),
         )
-        .unwrap();
-
-        let animes = extract_anime_data(&document, &SEARCH_SELECTOR);
-        let most_popular_animes = extract_side_bar_animes(&document, &most_popular_selector);
-        let total_pages = get_last_page_no(&document);
-        let current_page = page_no;
-        let has_next_page = page_no != total_pages;
-        let genres = extract_genres(&document, &GENRES_SELECTOR);
-
-        Ok(SearchInfo {
-            total_pages,
-            current_page,
-            has_next_page,
-            animes,
-            most_popular_animes,
-            genres,
-        })
+        .await
     }
 
-    pub async fn scrape_episodes(&self, id: &str) -> Result<EpisodesInfo, AniRustError> {
-        let mut error_vec = vec![];
-        let mut curl = String::new();
-        let anime_id = id.split('-').last().unwrap();
+    /// Live suggestions for `query`, as shown under the site's search box.
+    ///
+    /// A blank query returns no suggestions without sending a request.
+    pub async fn scrape_search_suggestions(
+        &self,
+        query: &str,
+    ) -> Result<Vec<SearchSuggestion>, AniRustError> {
+        if query.trim().is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut errors = vec![];
+        // Queries without a match get an empty list, so an empty body is a success
+        let mut curl = None;
+        let mut page_url = String::new();
+        let mut pending = None;
 
         for domain in &self.domains {
-            let url = format!("{}/ajax/v2/episode/list/{}", domain, anime_id);
+            let url = build_url(
+                domain,
+                &["ajax", "search", "suggest"],
+                &[("keyword", query)],
+            );
 
-            match get_ajax_curl(&url, "html").await {
-                Ok(curl_string) => {
-                    curl = curl_string;
+            match self.get_ajax(CacheKind::Search, &url, "html").await {
+                Ok(fetched) => {
+                    curl = Some(fetched.body);
+                    pending = fetched.pending;
+                    page_url = url;
                     break;
                 }
-                Err(e) => {
-                    error_vec.push(Some(e));
+                Err(error @ AniRustError::NotFound { .. }) => return Err(self.report(error)),
+                Err(error) => errors.push(DomainError {
+                    domain: domain.clone(),
+                    error,
+                }),
+            }
+        }
+
+        let Some(curl) = curl else {
+            return Err(self.report(AniRustError::AllDomainsFailed(errors)));
+        };
+
+        self.checked(
+            pending,
+            &page_url,
+            &curl,
+            parse_search_suggestions(&self.config().selectors, &curl),
+        )
+        .await
+    }
+
+    /// Airing schedule of `date`, with air times in `tz_offset`.
+    pub async fn scrape_schedule(
+        &self,
+        date: NaiveDate,
+        tz_offset: FixedOffset,
+    ) -> Result<Vec<ScheduledAnime>, AniRustError> {
+        let mut errors = vec![];
+        // Days without releases get an empty list, so an empty body is a success
+        let mut curl = None;
+        let mut page_url = String::new();
+        let mut pending = None;
+        // The site expects JavaScript's `getTimezoneOffset`: UTC minus local time, in minutes
+        let js_offset = (-tz_offset.local_minus_utc() / 60).to_string();
+        let date_param = date.format("%Y-%m-%d").to_string();
+
+        for domain in &self.domains {
+            let url = build_url(
+                domain,
+                &["ajax", "schedule", "list"],
+                &[("tzOffset", &js_offset), ("date", &date_param)],
+            );
+
+            match self.get_ajax(CacheKind::Schedule, &url, "html").await {
+                Ok(fetched) => {
+                    curl = Some(fetched.body);
+                    pending = fetched.pending;
+                    page_url = url;
+                    break;
+                }
+                Err(error @ AniRustError::NotFound { .. }) => return Err(self.report(error)),
+                Err(error) => errors.push(DomainError {
+                    domain: domain.clone(),
+                    error,
+                }),
+            }
+        }
+
+        let Some(curl) = curl else {
+            return Err(self.report(AniRustError::AllDomainsFailed(errors)));
+        };
+
+        self.checked(
+            pending,
+            &page_url,
+            &curl,
+            parse_schedule(&self.config().selectors, &curl, date, tz_offset),
+        )
+        .await
+    }
+
+    /// Browses the `/filter` page; results are parsed like [`scrape_search`](Self::scrape_search).
+    pub async fn scrape_filter(
+        &self,
+        filter: &SearchFilter,
+        page_no: u32,
+    ) -> Result<SearchInfo, AniRustError> {
+        let mut errors = vec![];
+        let mut curl = String::new();
+        let mut page_url = String::new();
+        let mut pending = None;
+        let mut params = filter.query_pairs();
+        params.push(("page", page_no.to_string()));
+        let params: Vec<(&str, &str)> = params
+            .iter()
+            .map(|(name, value)| (*name, value.as_str()))
+            .collect();
+
+        for domain in &self.domains {
+            let url = build_url(domain, &["filter"], &params);
+
+            match self.get_page(CacheKind::Search, &url).await {
+                Ok(fetched) => {
+                    curl = fetched.body;
+                    pending = fetched.pending;
+                    page_url = url;
+                    break;
                 }
+                Err(error @ AniRustError::NotFound { .. }) => return Err(self.report(error)),
+                Err(error) => errors.push(DomainError {
+                    domain: domain.clone(),
+                    error,
+                }),
             }
         }
 
         if curl.is_empty() {
-            let error_string: String = anirust_error_vec_to_string(error_vec);
-            return Err(AniRustError::UnknownError(error_string));
+            return Err(self.report(AniRustError::AllDomainsFailed(errors)));
         }
 
-        let document = Html::parse_document(&curl);
+        self.checked(
+            pending,
+            &page_url,
+            &curl,
+            parse_search(&self.config().selectors, &curl, page_no),
+        )
+        .await
+    }
 
-        let episodes = extract_anime_episode(&document, &EPISODE_SELECTOR);
-        let total_episodes = episodes.len() as u32;
+    pub async fn scrape_episodes(&self, id: &str) -> Result<EpisodesInfo, AniRustError> {
+        validate_id(id).map_err(|e| self.report(e))?;
+        let mut errors = vec![];
+        let mut curl = String::new();
+        let mut page_url = String::new();
+        let mut pending = None;
+        let anime_id = id.split('-').next_back().unwrap();
+        let episodes_kind = match &self.cache {
+            Some(cache) => cache.episodes_kind(anime_id),
+            None => CacheKind::EpisodesAiring,
+        };
 
-        Ok(EpisodesInfo {
-            total_episodes,
-            episodes,
-        })
+        for domain in &self.domains {
+            let url = build_url(domain, &["ajax", "v2", "episode", "list", anime_id], &[]);
+
+            match self.get_ajax(episodes_kind, &url, "html").await {
+                Ok(fetched) => {
+                    curl = fetched.body;
+                    pending = fetched.pending;
+                    page_url = url;
+                    break;
+                }
+                Err(error @ AniRustError::NotFound { .. }) => return Err(self.report(error)),
+                Err(error) => errors.push(DomainError {
+                    domain: domain.clone(),
+                    error,
+                }),
+            }
+        }
+
+        if curl.is_empty() {
+            return Err(self.report(AniRustError::AllDomainsFailed(errors)));
+        }
+
+        self.checked(
+            pending,
+            &page_url,
+            &curl,
+            parse_episodes(&self.config().selectors, &curl),
+        )
+        .await
     }
 
     pub async fn scrape_servers(&self, id: &str) -> Result<ServerInfo, AniRustError> {
-        let mut error_vec = vec![];
+        let mut errors = vec![];
         let mut curl = String::new();
+        let mut page_url = String::new();
+        let mut pending = None;
         let episode_id = id.split("ep=").last().unwrap_or_default();
+        validate_id(id)
+            .and_then(|_| validate_id(episode_id))
+            .map_err(|e| self.report(e))?;
 
         for domain in &self.domains {
-            let url = format!(
-                "{}/ajax/v2/episode/servers?episodeId={}",
-                domain, episode_id
+            let url = build_url(
+                domain,
+                &["ajax", "v2", "episode", "servers"],
+                &[("episodeId", episode_id)],
             );
 
-            match get_ajax_curl(&url, "html").await {
-                Ok(curl_string) => {
-                    curl = curl_string;
+            match self.get_ajax(CacheKind::Servers, &url, "html").await {
+                Ok(fetched) => {
+                    curl = fetched.body;
+                    pending = fetched.pending;
+                    page_url = url;
                     break;
                 }
-                Err(e) => {
-                    error_vec.push(Some(e));
-                }
+                Err(error @ AniRustError::NotFound { .. }) => return Err(self.report(error)),
+                Err(error) => errors.push(DomainError {
+                    domain: domain.clone(),
+                    error,
+                }),
             }
         }
 
         if curl.is_empty() {
-            let error_string: String = anirust_error_vec_to_string(error_vec);
-            return Err(AniRustError::UnknownError(error_string));
+            return Err(self.report(AniRustError::AllDomainsFailed(errors)));
         }
 
-        let document = Html::parse_document(&curl);
-
-        let episode_str = document
-            .select(&EPISODE_NO_SELECTOR)
-            .next()
-            .map(|e| e.text().collect::<String>().trim().to_string())
-            .unwrap_or_default();
-        let last_part = episode_str.split_whitespace().last().unwrap_or_default();
-
-        let episode_no = last_part.parse::<u32>().unwrap_or_default();
-        let sub = extract_episode_servers(&document, &EPISODE_SUB_SELECTOR);
-        let dub = extract_episode_servers(&document, &EPISODE_DUB_SELECTOR);
-        let raw = extract_episode_servers(&document, &EPISODE_RAW_SELECTOR);
-
-        Ok(ServerInfo {
-            episode_no,
-            sub,
-            dub,
-            raw,
-        })
+        self.checked(
+            pending,
+            &page_url,
+            &curl,
+            parse_servers(&self.config().selectors, &curl),
+        )
+        .await
     }
 
     pub async fn scrape_episode_server_source(
@@ -582,7 +1161,7 @@ impl HiAnimeRust {
         anime_server: Option<AnimeServer>,
     ) -> Result<ServerExtractedInfo, AniRustError> {
         let server_list = self.scrape_servers(id).await?;
-        let mut error_vec = vec![];
+        let mut errors = vec![];
         let mut link = String::new();
 
         let mut server_id: u32 = 0;
@@ -599,819 +1178,82 @@ impl HiAnimeRust {
         }
 
         for domain in &self.domains {
-            let url = format!("{}/ajax/v2/episode/sources?id={}", domain, data_id);
-            println!("{:?}", url);
+            let url = build_url(
+                domain,
+                &["ajax", "v2", "episode", "sources"],
+                &[("id", &data_id.to_string())],
+            );
 
-            match get_ajax_curl(&url, "link").await {
+            match get_ajax_curl(&self.transport, &url, "link").await {
                 Ok(curl_string) => {
                     link = curl_string;
                     break;
                 }
-                Err(e) => {
-                    error_vec.push(Some(e));
-                }
+                Err(error @ AniRustError::NotFound { .. }) => return Err(self.report(error)),
+                Err(error) => errors.push(DomainError {
+                    domain: domain.clone(),
+                    error,
+                }),
             }
         }
 
         if link.is_empty() {
-            let error_string: String = anirust_error_vec_to_string(error_vec);
-            return Err(AniRustError::UnknownError(error_string));
+            return Err(self.report(AniRustError::AllDomainsFailed(errors)));
         }
 
         let server_info = match server_id {
-            3 => StreamTapeServer::extract(&link, &self.proxies).await?,
-            4 => MegaCloudServer::extract(&link, &self.proxies).await?,
-            5 => MegaCloudServer::extract(&link, &self.proxies).await?,
-            1 => MegaCloudServer::extract(&link, &self.proxies).await?,
-            _ => MegaCloudServer::extract(&link, &self.proxies).await?,
+            3 => StreamTapeServer::extract(&link, &self.transport)
+                .await
+                .map_err(|e| self.report(e))?,
+            4 => MegaCloudServer::extract(&link, &self.transport)
+                .await
+                .map_err(|e| self.report(e))?,
+            5 => MegaCloudServer::extract(&link, &self.transport)
+                .await
+                .map_err(|e| self.report(e))?,
+            1 => MegaCloudServer::extract(&link, &self.transport)
+                .await
+                .map_err(|e| self.report(e))?,
+            _ => MegaCloudServer::extract(&link, &self.transport)
+                .await
+                .map_err(|e| self.report(e))?,
         };
 
         Ok(server_info)
     }
 }
 
-fn extract_anime_data(document: &Html, selector: &Selector) -> Vec<Anime> {
-    document
-        .select(selector)
-        .map(|element| {
-            let id = element
-                .select(&Selector::parse(".film-name .dynamic-name").unwrap())
-                .next()
-                .and_then(|e| e.value().attr("href"))
-                .map(|s| s.trim_start_matches('/').to_string())
-                .unwrap_or_default();
-
-            let title = element
-                .select(&Selector::parse(".film-name .dynamic-name").unwrap())
-                .next()
-                .map(|e| e.text().collect::<String>().trim().to_string())
-                .unwrap_or_default();
-
-            let subs = element
-                .select(&Selector::parse(".film-poster .tick-sub").unwrap())
-                .next()
-                .and_then(|e| e.text().collect::<String>().parse::<u32>().ok())
-                .unwrap_or_default();
-
-            let dubs = element
-                .select(&Selector::parse(".film-poster .tick-dub").unwrap())
-                .next()
-                .and_then(|e| e.text().collect::<String>().parse::<u32>().ok())
-                .unwrap_or_default();
-
-            let eps = element
-                .select(&Selector::parse(".film-poster .tick-eps").unwrap())
-                .next()
-                .and_then(|e| e.text().collect::<String>().parse::<u32>().ok())
-                .unwrap_or_default();
-
-            let duration = element
-                .select(&Selector::parse(".fd-infor .fdi-duration").unwrap())
-                .next()
-                .map(|e| e.text().collect::<String>().trim().to_string())
-                .unwrap_or_default();
-
-            let rating = element
-                .select(&Selector::parse(".film-poster .tick-rate").unwrap())
-                .next()
-                .map(|e| e.text().collect::<String>().trim().to_string())
-                .unwrap_or_default();
-
-            let image = element
-                .select(&Selector::parse(".film-poster .film-poster-img").unwrap())
-                .next()
-                .and_then(|e| e.value().attr("data-src").map(|s| s.to_string()))
-                .unwrap_or_default();
-
-            Anime {
-                id,
-                title,
-                subs,
-                dubs,
-                eps,
-                duration,
-                rating,
-                image,
-            }
-        })
-        .collect()
-}
-
-fn extract_spotlight_anime_data(document: &Html, selector: &Selector) -> Vec<SpotlightAnime> {
-    document
-        .select(selector)
-        .map(|element| {
-            let id = element
-                .select(&Selector::parse(".deslide-item-content .desi-buttons a").unwrap())
-                .next()
-                .and_then(|e| e.value().attr("href"))
-                .map(|s| s.trim_start_matches('/').to_string())
-                .unwrap_or_default()
-                .split("/")
-                .last()
-                .unwrap_or_default()
-                .to_string();
-
-            let title = element
-                .select(
-                    &Selector::parse(".deslide-item-content .desi-head-title.dynamic-name")
-                        .unwrap(),
-                )
-                .next()
-                .map(|e| e.text().collect::<String>().trim().to_string())
-                .unwrap_or_default();
-
-            let rank = element
-                .select(&Selector::parse(".deslide-item-content .desi-sub-text").unwrap())
-                .next()
-                .map(|e| e.text().collect::<String>().trim().to_string())
-                .unwrap_or_default()
-                .split_whitespace()
-                .next()
-                .and_then(|s| s.trim_start_matches('#').parse::<u32>().ok())
-                .unwrap_or_default();
-
-            let image = element
-                .select(
-                    &Selector::parse(".deslide-cover .deslide-cover-img .film-poster-img").unwrap(),
-                )
-                .next()
-                .and_then(|e| e.value().attr("data-src").map(|s| s.to_string()))
-                .unwrap_or_default();
-
-            let description = element
-                .select(&Selector::parse(".deslide-item-content .desi-description").unwrap())
-                .next()
-                .map(|e| e.text().collect::<String>().trim().to_string())
-                .unwrap_or_default();
-
-            let extra_info: Vec<String> = element
-                .select(&Selector::parse(".deslide-item-content .sc-detail .scd-item").unwrap())
-                .map(|e| e.text().collect::<String>().trim().to_string())
-                .collect();
-
-            let eps = extra_info
-                .get(4)
-                .and_then(|s| {
-                    s.split_whitespace()
-                        .map(|s| s.parse().ok())
-                        .collect::<Vec<_>>()
-                        .get(2)
-                        .copied()
-                })
-                .flatten()
-                .unwrap_or_default();
-
-            let subs = extra_info
-                .get(4)
-                .and_then(|s| {
-                    s.split_whitespace()
-                        .map(|s| s.parse().ok())
-                        .collect::<Vec<_>>()
-                        .first()
-                        .copied()
-                })
-                .flatten()
-                .unwrap_or_default();
-
-            let dubs = extra_info
-                .get(4)
-                .and_then(|s| {
-                    s.split_whitespace()
-                        .map(|s| s.parse().ok())
-                        .collect::<Vec<_>>()
-                        .get(1)
-                        .copied()
-                })
-                .flatten()
-                .unwrap_or_default();
-
-            SpotlightAnime {
-                id,
-                title,
-                rank,
-                image,
-                description,
-                subs,
-                dubs,
-                eps,
-                duration: extra_info.get(1).cloned().unwrap_or_default(),
-                quality: extra_info.get(3).cloned().unwrap_or_default(),
-                category: extra_info.first().cloned().unwrap_or_default(),
-                released_day: extra_info.get(2).cloned().unwrap_or_default(),
-            }
-        })
-        .collect()
-}
-
-fn extract_minimal_anime(document: &Html, selector: &Selector) -> Vec<MinimalAnime> {
-    document
-        .select(selector)
-        .map(|element| {
-            let id = element
-                .select(&Selector::parse(".item .film-poster").unwrap())
-                .next()
-                .and_then(|e| e.value().attr("href"))
-                .map(|href| href.trim_start_matches('/'))
-                .map(|s| s.to_string())
-                .unwrap_or_default();
-
-            let title = element
-                .select(&Selector::parse(".item .number .film-title.dynamic-name").unwrap())
-                .next()
-                .map(|e| e.text().collect::<String>().trim().to_string())
-                .unwrap_or_default();
-
-            let image = element
-                .select(&Selector::parse(".item .film-poster .film-poster-img").unwrap())
-                .next()
-                .and_then(|e| e.value().attr("data-src"))
-                .map(|s| s.trim().to_string())
-                .unwrap_or_default();
-
-            MinimalAnime { id, title, image }
-        })
-        .collect()
-}
-
-fn extract_featured_anime(
-    document: &Html,
-    selector: &Selector,
-) -> (
-    Vec<MinimalAnime>,
-    Vec<MinimalAnime>,
-    Vec<MinimalAnime>,
-    Vec<MinimalAnime>,
-) {
-    let id_selector = Selector::parse(".film-detail .film-name .dynamic-name").unwrap();
-    let image_selector = Selector::parse(".film-poster a .film-poster-img").unwrap();
-
-    let res: Vec<MinimalAnime> = document
-        .select(selector)
-        .map(|element| {
-            let id = element
-                .select(&id_selector)
-                .next()
-                .and_then(|e| e.value().attr("href"))
-                .map(|href| href.trim_start_matches('/').to_string())
-                .unwrap_or_default();
-
-            let title = element
-                .select(&id_selector)
-                .next()
-                .map(|e| e.text().collect::<String>().trim().to_string())
-                .unwrap_or_default();
-
-            let image = element
-                .select(&image_selector)
-                .next()
-                .and_then(|e| e.value().attr("data-src"))
-                .map(|s| s.trim().to_string())
-                .unwrap_or_default();
-
-            MinimalAnime { id, title, image }
-        })
-        .collect();
-
-    let top_airing_animes = res[0..5].to_vec();
-    let most_popular_animes = res[5..10].to_vec();
-    let most_favorite_animes = res[10..15].to_vec();
-    let latest_completed_animes = res[15..20].to_vec();
-
-    (
-        top_airing_animes,
-        most_popular_animes,
-        most_favorite_animes,
-        latest_completed_animes,
-    )
-}
-
-fn extract_top_10(document: &Html, selector: &Selector) -> Top10PeriodRankedAnime {
-    let (day, week, month) = document
-        .select(selector)
-        .filter_map(|element| element.value().attr("id"))
-        .map(|id| id.split('-').last().unwrap_or("").trim().to_string())
-        .fold(
-            (vec![], vec![], vec![]),
-            |(mut day, mut week, mut month), period_type| {
-                match period_type.as_str() {
-                    "week" => week.extend(extract_top_10_by_period_type(document, "week")),
-                    "month" => month.extend(extract_top_10_by_period_type(document, "month")),
-                    _ => day.extend(extract_top_10_by_period_type(document, "day")),
-                }
-                (day, week, month)
-            },
-        );
-
-    Top10PeriodRankedAnime { day, week, month }
-}
-
-fn extract_top_10_by_period_type(document: &Html, period_type: &str) -> Vec<Top10Anime> {
-    let selector_format = format!("#top-viewed-{} ul li", period_type);
-    let selector = Selector::parse(&selector_format).unwrap();
-
-    document
-        .select(&selector)
-        .map(|element| {
-            let id = element
-                .select(&Selector::parse(".film-detail .film-name .dynamic-name").unwrap())
-                .next()
-                .and_then(|e| e.value().attr("href"))
-                .map(|s| s.trim_start_matches('/').to_string())
-                .unwrap_or_default();
-
-            let title = element
-                .select(&Selector::parse(".film-detail .film-name .dynamic-name").unwrap())
-                .next()
-                .map(|e| e.text().collect::<String>().trim().to_string())
-                .unwrap_or_default();
-
-            let rank = element
-                .select(&Selector::parse(".film-number span").unwrap())
-                .next()
-                .map(|e| e.text().collect::<String>().trim().to_string())
-                .and_then(|e| e.parse::<u32>().ok())
-                .unwrap_or_default();
-
-            let subs = element
-                .select(&Selector::parse(".film-detail .fd-infor .tick-item.tick-sub").unwrap())
-                .next()
-                .and_then(|e| e.text().collect::<String>().parse::<u32>().ok())
-                .unwrap_or_default();
-
-            let dubs = element
-                .select(&Selector::parse(".film-detail .fd-infor .tick-item.tick-dub").unwrap())
-                .next()
-                .and_then(|e| e.text().collect::<String>().parse::<u32>().ok())
-                .unwrap_or_default();
-
-            let eps = element
-                .select(&Selector::parse(".film-detail .fd-infor .tick-item.tick-eps").unwrap())
-                .next()
-                .and_then(|e| e.text().collect::<String>().parse::<u32>().ok())
-                .unwrap_or_default();
-
-            let image = element
-                .select(&Selector::parse(".film-poster .film-poster-img").unwrap())
-                .next()
-                .and_then(|e| e.value().attr("data-src").map(|s| s.to_string()))
-                .unwrap_or_default();
-
-            Top10Anime {
-                id,
-                title,
-                image,
-                subs,
-                dubs,
-                eps,
-                rank,
-            }
-        })
-        .collect()
+// Appends each of `path` to `domain` as one segment and `query` as query pairs,
+// percent-encoding both
+fn build_url(domain: &str, path: &[&str], query: &[(&str, &str)]) -> String {
+    let mut url = Url::parse(domain).expect("domains are validated when the client is built");
+    url.path_segments_mut()
+        .expect("domains are http(s) URLs")
+        .pop_if_empty()
+        .extend(path);
+    if !query.is_empty() {
+        url.query_pairs_mut().extend_pairs(query);
+    }
+    url.into()
 }
 
-fn extract_anime_about_info(document: &Html, selector: &Selector) -> AboutAnime {
-    let play_button_selector = Selector::parse(".anisc-detail .film-buttons a.btn-play").unwrap();
-    let name_selector = Selector::parse(".anisc-detail .film-name.dynamic-name").unwrap();
-    let rating_selector = Selector::parse(".film-stats .tick .tick-pg").unwrap();
-    let quality_selector = Selector::parse(".film-stats .tick .tick-quality").unwrap();
-    let subs_selector = Selector::parse(".film-stats .tick .tick-sub").unwrap();
-    let dubs_selector = Selector::parse(".film-stats .tick .tick-dub").unwrap();
-    let eps_selector = Selector::parse(".film-stats .tick .tick-eps").unwrap();
-    let image_selector = Selector::parse(".anisc-poster .film-poster .film-poster-img").unwrap();
-    let description_selector = Selector::parse(".anisc-detail .film-description .text").unwrap();
-    let tick_selector = Selector::parse(".film-stats .tick").unwrap();
-    let json_script_selector = Selector::parse("#syncData").unwrap();
-    let more_info_selector = Selector::parse(
-        "#ani_detail .ani_detail-stage .container .anis-content .anisc-info .item-title",
-    )
-    .unwrap();
-    let genres_selector = Selector::parse(
-        "#ani_detail .ani_detail-stage .container .anis-content .anisc-info .item-list",
-    )
-    .unwrap();
-
-    let mut about_anime = AboutAnime {
-        id: String::new(),
-        mal_id: 0,
-        anime_id: 0,
-        al_id: 0,
-        title: String::new(),
-        description: String::new(),
-        image: String::new(),
-        category: String::new(),
-        rating: String::new(),
-        quality: String::new(),
-        duration: String::new(),
-        subs: 0,
-        dubs: 0,
-        eps: 0,
-        japanese: String::new(),
-        synonyms: String::new(),
-        aired: String::new(),
-        premiered: String::new(),
-        status: String::new(),
-        mal_score: String::new(),
-        studios: vec![],
-        producers: vec![],
-        genres: vec![],
-        most_popular_animes: vec![],
-        related_animes: vec![],
-        recommended_animes: vec![],
-        seasons: vec![],
+// Ids end up as a path segment or query value, so anything that could escape
+// that segment is rejected rather than encoded
+fn validate_id(id: &str) -> Result<(), AniRustError> {
+    let reason = if id.trim().is_empty() {
+        "must not be empty"
+    } else if id.contains(['/', '\\']) {
+        "must not contain path separators"
+    } else if id == "." || id == ".." {
+        "must not be a relative path"
+    } else {
+        return Ok(());
     };
 
-    document.select(selector).for_each(|element| {
-        about_anime.id = element
-            .select(&play_button_selector)
-            .next()
-            .and_then(|e| e.value().attr("href"))
-            .map(|s| s.split('/').last().unwrap_or("").to_string())
-            .unwrap_or_default();
-
-        about_anime.title = element
-            .select(&name_selector)
-            .next()
-            .map(|e| e.text().collect::<String>().trim().to_string())
-            .unwrap_or_default();
-
-        about_anime.rating = element
-            .select(&rating_selector)
-            .next()
-            .map(|e| e.text().collect::<String>().trim().to_string())
-            .unwrap_or_default();
-
-        about_anime.quality = element
-            .select(&quality_selector)
-            .next()
-            .map(|e| e.text().collect::<String>().trim().to_string())
-            .unwrap_or_default();
-
-        about_anime.subs = element
-            .select(&subs_selector)
-            .next()
-            .and_then(|e| e.text().collect::<String>().parse::<u32>().ok())
-            .unwrap_or_default();
-
-        about_anime.dubs = element
-            .select(&dubs_selector)
-            .next()
-            .and_then(|e| e.text().collect::<String>().parse::<u32>().ok())
-            .unwrap_or_default();
-
-        about_anime.eps = element
-            .select(&eps_selector)
-            .next()
-            .and_then(|e| e.text().collect::<String>().parse::<u32>().ok())
-            .unwrap_or_default();
-
-        about_anime.image = element
-            .select(&image_selector)
-            .next()
-            .and_then(|e| e.value().attr("src").map(|s| s.to_string()))
-            .unwrap_or_default();
-
-        about_anime.description = element
-            .select(&description_selector)
-            .next()
-            .map(|e| e.text().collect::<String>().trim().to_string())
-            .unwrap_or_default();
-
-        if let Some(tick) = element.select(&tick_selector).next() {
-            let text = tick
-                .text()
-                .collect::<String>()
-                .replace('\n', " ")
-                .trim()
-                .to_string();
-
-            let mut parts = text.split_whitespace().rev();
-            about_anime.category = parts.nth(1).unwrap_or("").to_string();
-            about_anime.duration = parts.next().unwrap_or("").to_string();
-        }
-
-        let json_text = document
-            .select(&json_script_selector)
-            .next()
-            .map(|script| script.text().collect::<String>())
-            .unwrap_or_default();
-
-        if let Ok(json) = serde_json::from_str::<Value>(&json_text) {
-            about_anime.anime_id = json
-                .get("anime_id")
-                .and_then(Value::as_str)
-                .unwrap_or("")
-                .parse::<u32>()
-                .unwrap_or_default();
-
-            about_anime.mal_id = json
-                .get("mal_id")
-                .and_then(Value::as_str)
-                .unwrap_or("")
-                .parse::<u32>()
-                .unwrap_or_default();
-
-            about_anime.al_id = json
-                .get("anilist_id")
-                .and_then(Value::as_str)
-                .unwrap_or("")
-                .parse::<u32>()
-                .unwrap_or_default();
-        }
-    });
-
-    document.select(&more_info_selector).for_each(|element| {
-        let head = element
-            .select(&Selector::parse(".item-head").unwrap())
-            .next()
-            .map(|e| e.text().collect::<String>().trim().to_string())
-            .unwrap_or_default();
-
-        let key = element
-            .select(&Selector::parse(".name").unwrap())
-            .next()
-            .map(|e| e.text().collect::<String>().trim().to_string())
-            .unwrap_or_default();
-
-        match head.as_str() {
-            "Japanese:" => about_anime.japanese = key,
-            "Synonyms:" => about_anime.synonyms = key,
-            "Aired:" => about_anime.aired = key,
-            "Premiered:" => about_anime.premiered = key,
-            "Status:" => about_anime.status = key,
-            "MAL Score:" => about_anime.mal_score = key,
-            "Producers:" => {
-                about_anime.producers.extend(
-                    element
-                        .select(&Selector::parse("a.name").unwrap())
-                        .map(|e| e.text().collect::<String>().trim().to_string()),
-                );
-            }
-            "Studios:" => {
-                about_anime.studios.extend(
-                    element
-                        .select(&Selector::parse("a.name").unwrap())
-                        .map(|e| e.text().collect::<String>().trim().to_string()),
-                );
-            }
-            _ => {}
-        }
-    });
-
-    document.select(&genres_selector).for_each(|element| {
-        about_anime.genres.extend(
-            element
-                .select(&Selector::parse("a").unwrap())
-                .map(|e| e.text().collect::<String>().trim().to_string()),
-        );
-    });
-
-    about_anime
-        .most_popular_animes
-        .extend(extract_side_bar_animes(
-            document,
-            &MOST_POPULAR_ANIME_SELECTOR,
-        ));
-    about_anime
-        .related_animes
-        .extend(extract_side_bar_animes(document, &RELATED_ANIME_SELECTOR));
-    about_anime
-        .recommended_animes
-        .extend(extract_anime_data(document, &RECOMMENDED_ANIME_SELECTOR));
-    about_anime
-        .seasons
-        .extend(extract_anime_seasons(document, &SEASONS_SELECTOR));
-
-    about_anime
-}
-
-fn extract_side_bar_animes(document: &Html, selector: &Selector) -> Vec<SideBarAnimes> {
-    let dynamic_name_selector = Selector::parse(".film-detail .dynamic-name").unwrap();
-    let tick_selector = Selector::parse(".fd-infor .tick").unwrap();
-    let tick_item_sub_selector = Selector::parse(".fd-infor .tick .tick-item.tick-sub").unwrap();
-    let tick_item_dub_selector = Selector::parse(".fd-infor .tick .tick-item.tick-dub").unwrap();
-    let tick_item_eps_selector = Selector::parse(".fd-infor .tick .tick-item.tick-eps").unwrap();
-    let film_poster_selector = Selector::parse(".film-poster .film-poster-img").unwrap();
-
-    document
-        .select(selector)
-        .map(|element| {
-            let id = element
-                .select(&dynamic_name_selector)
-                .next()
-                .and_then(|e| e.value().attr("href"))
-                .map(|s| s.trim_start_matches('/').to_string())
-                .unwrap_or_default();
-
-            let title = element
-                .select(&dynamic_name_selector)
-                .next()
-                .map(|e| e.text().collect::<String>().trim().to_string())
-                .unwrap_or_default();
-
-            let image = element
-                .select(&film_poster_selector)
-                .next()
-                .and_then(|e| e.value().attr("data-src").map(|s| s.to_string()))
-                .unwrap_or_default();
-
-            let subs = element
-                .select(&tick_item_sub_selector)
-                .next()
-                .and_then(|e| e.text().collect::<String>().trim().parse::<u32>().ok())
-                .unwrap_or_default();
-
-            let dubs = element
-                .select(&tick_item_dub_selector)
-                .next()
-                .and_then(|e| e.text().collect::<String>().trim().parse::<u32>().ok())
-                .unwrap_or_default();
-
-            let eps = element
-                .select(&tick_item_eps_selector)
-                .next()
-                .and_then(|e| e.text().collect::<String>().trim().parse::<u32>().ok())
-                .unwrap_or_default();
-
-            let category = element
-                .select(&tick_selector)
-                .next()
-                .map(|e| e.text().collect::<String>().trim().to_string())
-                .map(|s| s.replace('\n', " ").replace("  ", " ").trim().to_string())
-                .map(|s| s.split_whitespace().last().unwrap_or_default().to_string())
-                .unwrap_or_default();
-
-            SideBarAnimes {
-                id,
-                title,
-                image,
-                subs,
-                dubs,
-                eps,
-                category,
-            }
-        })
-        .collect()
-}
-
-fn extract_anime_seasons(document: &Html, selector: &Selector) -> Vec<AnimeSeason> {
-    document
-        .select(selector)
-        .map(|element| {
-            let id = element
-                .value()
-                .attr("href")
-                .map(|s| s.trim_start_matches('/').to_string())
-                .unwrap_or_default();
-
-            let title = element
-                .value()
-                .attr("title")
-                .map(|e| e.trim().to_string())
-                .unwrap_or_default();
-
-            let anime_title = element
-                .select(&Selector::parse(".title").unwrap())
-                .next()
-                .map(|e| e.text().collect::<String>().trim().to_string())
-                .unwrap_or_default();
-
-            let mut image = element
-                .select(&Selector::parse(".season-poster").unwrap())
-                .next()
-                .and_then(|e| e.value().attr("style").map(|s| s.to_string()))
-                .unwrap_or_default();
-
-            let re = Regex::new(r"url\((?P<url>.*?)\)").unwrap();
-
-            image = re
-                .captures(&image)
-                .and_then(|caps| caps.name("url"))
-                .map(|m| m.as_str().trim_matches('"').to_string())
-                .unwrap_or_default();
-
-            let is_current = element.has_class("active");
-
-            AnimeSeason {
-                id,
-                title,
-                anime_title,
-                image,
-                is_current,
-            }
-        })
-        .collect()
-}
-
-fn extract_anime_episode(document: &Html, selector: &Selector) -> Vec<AnimeEpisode> {
-    document
-        .select(selector)
-        .filter_map(|element| {
-            let id = element
-                .value()
-                .attr("href")
-                .map(|s| s.trim_start_matches('/').to_string())
-                .unwrap_or_default()
-                .split('/')
-                .last()
-                .unwrap_or_default()
-                .to_string();
-
-            if id.is_empty() {
-                return None;
-            }
-
-            let title = element
-                .value()
-                .attr("title")
-                .map(|e| e.trim().to_string())
-                .unwrap_or_default();
-
-            let episode_no = element
-                .value()
-                .attr("data-number")
-                .and_then(|e| e.trim().parse::<u32>().ok())
-                .unwrap_or(0);
-
-            let is_filler = element.has_class("ssl-item-filler");
-
-            Some(AnimeEpisode {
-                id,
-                title,
-                episode_no,
-                is_filler,
-            })
-        })
-        .collect()
-}
-
-fn extract_episode_servers(document: &Html, selector: &Selector) -> Vec<Server> {
-    document
-        .select(selector)
-        .map(|element| {
-            let server_name = element
-                .select(&Selector::parse("a").unwrap())
-                .next()
-                .map(|e| {
-                    e.text()
-                        .collect::<String>()
-                        .to_lowercase()
-                        .trim()
-                        .to_string()
-                })
-                .unwrap_or_default();
-
-            let data_id = element
-                .attr("data-id")
-                .and_then(|id| id.trim().parse::<u32>().ok())
-                .unwrap_or_default();
-
-            let server_id = element
-                .attr("data-server-id")
-                .and_then(|id| id.trim().parse::<u32>().ok())
-                .unwrap_or_default();
-
-            Server {
-                server_name,
-                server_id,
-                data_id,
-            }
-        })
-        .collect()
-}
-
-fn extract_genres(document: &Html, selector: &Selector) -> Vec<String> {
-    document
-        .select(selector)
-        .map(|element| {
-            let text = element.text().collect::<String>().trim().to_string();
-            if text.is_empty() {
-                String::new()
-            } else {
-                text
-            }
-        })
-        .collect()
-}
-
-// Function to extract the last page number from the response
-fn get_last_page_no(document: &Html) -> u32 {
-    document
-        .select(&NAVIGATION_SELECTOR)
-        .last()
-        .and_then(|element| element.value().attr("href"))
-        .and_then(|href| href.split('=').last())
-        .and_then(|page_str| page_str.parse::<u32>().ok())
-        .unwrap_or(1)
-}
-
-fn initialize_secret(secret: Option<SecretConfig>) -> Option<SecretConfig> {
-    let mut secret_lock = env::SECRET.lock().unwrap();
-    secret_lock.clone_from(&secret);
-    let secret_clone = secret_lock.clone();
-    // Release the lock.
-    drop(secret_lock);
-    secret_clone
+    Err(AniRustError::InvalidId {
+        id: id.to_string(),
+        reason: reason.to_string(),
+    })
 }
 
 fn update_server_id(
@@ -1423,7 +1265,6 @@ fn update_server_id(
     let anime_server = anime_server.unwrap_or(AnimeServer::Vidstreaming);
 
     for server in servers {
-        println!("{} - {}", server.server_name, anime_server.as_str());
         if server.server_name == anime_server.as_str() {
             *server_id = server.server_id;
             *data_id = server.data_id;
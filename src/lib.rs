@@ -1,9 +1,19 @@
 // src/lib.rs
 
+pub mod cache;
+pub mod config;
 pub mod env;
 pub mod error;
-mod proxy;
+pub mod filter;
+pub mod parse;
+pub mod proxy;
+pub mod rate_limit;
+pub mod replay;
+pub mod reporter;
+pub mod retry;
+pub mod selectors;
 pub mod servers;
+pub mod transport;
 mod utils;
 
 pub mod hianime;
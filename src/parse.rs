@@ -0,0 +1,1825 @@
+// parse.rs
+
+use std::fmt;
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use regex::Regex;
+use scraper::{selectable::Selectable, Html, Selector};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    hianime::{
+        AboutAnime, Anime, AnimeEpisode, AnimeQtip, AnimeSeason, AtoZ, CategoryInfo, Character,
+        CharactersInfo, EpisodesInfo, FeaturedAnime, HomeInfo, MinimalAnime, ScheduledAnime,
+        SearchInfo, SearchSuggestion, Server, ServerInfo, SideBarAnimes, SpotlightAnime,
+        Top10Anime, Top10PeriodRankedAnime, VoiceActor,
+    },
+    selectors::Selectors,
+};
+
+/// How `HiAnimeRust` treats pages whose [`ParseDiagnostics`] are not clean
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Return whatever was parsed
+    #[default]
+    Lenient,
+    /// Return whatever was parsed and pass an [`AniRustError::IncompleteParse`]
+    /// to the error reporter
+    ///
+    /// [`AniRustError::IncompleteParse`]: crate::error::AniRustError::IncompleteParse
+    Report,
+    /// Fail with [`AniRustError::IncompleteParse`]
+    ///
+    /// [`AniRustError::IncompleteParse`]: crate::error::AniRustError::IncompleteParse
+    Strict,
+}
+
+/// Data parsed from a page, with what was missing or malformed on it
+#[derive(Debug, Clone)]
+pub struct Parsed<T> {
+    pub data: T,
+    pub diagnostics: ParseDiagnostics,
+}
+
+/// Signs that a page no longer matches the markup the parser expects.
+///
+/// Fields are named after the path of the value in the parsed data, such as
+/// `latest_episodes.title` or `top_10_animes.day.rank`, and listed once each.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParseDiagnostics {
+    /// Selectors expected on the page that matched nothing
+    pub missing_selectors: Vec<String>,
+    /// Required fields that came back empty
+    pub empty_fields: Vec<String>,
+    /// Numeric and time fields whose text could not be read
+    pub invalid_numbers: Vec<InvalidNumber>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InvalidNumber {
+    pub field: String,
+    /// First offending text found for the field
+    pub text: String,
+}
+
+impl ParseDiagnostics {
+    /// Whether the page was parsed without any sign of markup drift.
+    pub fn is_clean(&self) -> bool {
+        self.missing_selectors.is_empty()
+            && self.empty_fields.is_empty()
+            && self.invalid_numbers.is_empty()
+    }
+
+    // Records `selector` when it matches nothing in `document`
+    fn require(&mut self, document: &Html, selector: &Selector) {
+        if document.select(selector).next().is_none() {
+            let css = scraper::selector::ToCss::to_css_string(selector);
+            if !self.missing_selectors.contains(&css) {
+                self.missing_selectors.push(css);
+            }
+        }
+    }
+
+    fn empty(&mut self, field: String) {
+        if !self.empty_fields.contains(&field) {
+            self.empty_fields.push(field);
+        }
+    }
+
+    // Records the required fields of `items` that are empty
+    fn check<T: Required>(&mut self, section: &str, items: &[T]) {
+        for item in items {
+            for field in item.empty_fields() {
+                self.empty(format!("{}.{}", section, field));
+            }
+        }
+    }
+
+    // Parses a count; absent or blank text is 0, anything else that is not a number is recorded
+    fn number(&mut self, section: &str, field: &str, text: Option<impl AsRef<str>>) -> u32 {
+        let Some(text) = text else {
+            return 0;
+        };
+        let text = text.as_ref().trim();
+        if text.is_empty() {
+            return 0;
+        }
+
+        text.parse::<u32>().unwrap_or_else(|_| {
+            self.invalid(format!("{}.{}", section, field), text);
+            0
+        })
+    }
+
+    fn invalid(&mut self, field: String, text: &str) {
+        if !self.invalid_numbers.iter().any(|n| n.field == field) {
+            self.invalid_numbers.push(InvalidNumber {
+                field,
+                text: text.to_string(),
+            });
+        }
+    }
+}
+
+impl fmt::Display for ParseDiagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = vec![];
+        if !self.missing_selectors.is_empty() {
+            parts.push(format!(
+                "selectors matched nothing: {}",
+                self.missing_selectors.join(", ")
+            ));
+        }
+        if !self.empty_fields.is_empty() {
+            parts.push(format!("empty fields: {}", self.empty_fields.join(", ")));
+        }
+        if !self.invalid_numbers.is_empty() {
+            let numbers: Vec<String> = self
+                .invalid_numbers
+                .iter()
+                .map(|n| format!("{} ({:?})", n.field, n.text))
+                .collect();
+            parts.push(format!("invalid numbers: {}", numbers.join(", ")));
+        }
+
+        if parts.is_empty() {
+            write!(f, "no issues")
+        } else {
+            write!(f, "{}", parts.join("; "))
+        }
+    }
+}
+
+// Fields of a parsed item that must not be empty
+trait Required {
+    fn empty_fields(&self) -> Vec<&'static str>;
+}
+
+fn empty_strings<const N: usize>(fields: [(&'static str, &str); N]) -> Vec<&'static str> {
+    fields
+        .into_iter()
+        .filter(|(_, value)| value.is_empty())
+        .map(|(name, _)| name)
+        .collect()
+}
+
+impl Required for Anime {
+    fn empty_fields(&self) -> Vec<&'static str> {
+        empty_strings([
+            ("id", &self.id),
+            ("title", &self.title),
+            ("image", &self.image),
+        ])
+    }
+}
+
+impl Required for MinimalAnime {
+    fn empty_fields(&self) -> Vec<&'static str> {
+        empty_strings([
+            ("id", &self.id),
+            ("title", &self.title),
+            ("image", &self.image),
+        ])
+    }
+}
+
+impl Required for SpotlightAnime {
+    fn empty_fields(&self) -> Vec<&'static str> {
+        let mut fields = empty_strings([
+            ("id", &self.id),
+            ("title", &self.title),
+            ("image", &self.image),
+        ]);
+        if self.rank == 0 {
+            fields.push("rank");
+        }
+        fields
+    }
+}
+
+impl Required for Top10Anime {
+    fn empty_fields(&self) -> Vec<&'static str> {
+        let mut fields = empty_strings([
+            ("id", &self.id),
+            ("title", &self.title),
+            ("image", &self.image),
+        ]);
+        if self.rank == 0 {
+            fields.push("rank");
+        }
+        fields
+    }
+}
+
+impl Required for SideBarAnimes {
+    fn empty_fields(&self) -> Vec<&'static str> {
+        empty_strings([
+            ("id", &self.id),
+            ("title", &self.title),
+            ("image", &self.image),
+        ])
+    }
+}
+
+impl Required for SearchSuggestion {
+    fn empty_fields(&self) -> Vec<&'static str> {
+        empty_strings([("id", &self.id), ("title", &self.title)])
+    }
+}
+
+impl Required for ScheduledAnime {
+    fn empty_fields(&self) -> Vec<&'static str> {
+        let mut fields = empty_strings([("id", &self.id), ("title", &self.title)]);
+        if self.episode_no == 0 {
+            fields.push("episode_no");
+        }
+        fields
+    }
+}
+
+impl Required for Character {
+    fn empty_fields(&self) -> Vec<&'static str> {
+        empty_strings([("id", &self.id), ("name", &self.name)])
+    }
+}
+
+impl Required for VoiceActor {
+    fn empty_fields(&self) -> Vec<&'static str> {
+        empty_strings([("id", &self.id), ("name", &self.name)])
+    }
+}
+
+impl Required for AnimeQtip {
+    fn empty_fields(&self) -> Vec<&'static str> {
+        empty_strings([("id", &self.id), ("title", &self.title)])
+    }
+}
+
+impl Required for AnimeSeason {
+    fn empty_fields(&self) -> Vec<&'static str> {
+        empty_strings([("id", &self.id), ("title", &self.title)])
+    }
+}
+
+impl Required for AnimeEpisode {
+    fn empty_fields(&self) -> Vec<&'static str> {
+        let mut fields = empty_strings([("title", &self.title)]);
+        if self.episode_no == 0 {
+            fields.push("episode_no");
+        }
+        fields
+    }
+}
+
+impl Required for Server {
+    fn empty_fields(&self) -> Vec<&'static str> {
+        let mut fields = empty_strings([("server_name", &self.server_name)]);
+        if self.data_id == 0 {
+            fields.push("data_id");
+        }
+        fields
+    }
+}
+
+impl Required for AboutAnime {
+    fn empty_fields(&self) -> Vec<&'static str> {
+        let mut fields = empty_strings([
+            ("id", &self.id),
+            ("title", &self.title),
+            ("image", &self.image),
+            ("status", &self.status),
+        ]);
+        if self.anime_id == 0 {
+            fields.push("anime_id");
+        }
+        fields
+    }
+}
+
+trait HasClass {
+    fn has_class(&self, class_name: &str) -> bool;
+}
+
+impl HasClass for scraper::ElementRef<'_> {
+    fn has_class(&self, class_name: &str) -> bool {
+        self.value()
+            .attr("class")
+            .map(|class| class.split_whitespace().any(|c| c == class_name))
+            .unwrap_or(false)
+    }
+}
+
+/// Parses the home page.
+pub fn parse_home(selectors: &Selectors, html: &str) -> Parsed<HomeInfo> {
+    let document = Html::parse_document(html);
+    let mut diagnostics = ParseDiagnostics::default();
+    for selector in [
+        &selectors.trending,
+        &selectors.latest_episodes,
+        &selectors.top_upcoming,
+        &selectors.spotlight,
+        &selectors.featured,
+        &selectors.top_10,
+        &selectors.genres,
+    ] {
+        diagnostics.require(&document, selector);
+    }
+
+    let trending = extract_minimal_anime(&document, selectors, &selectors.trending);
+    diagnostics.check("trending", &trending);
+    let latest_episodes = extract_anime_data(
+        &document,
+        selectors,
+        &selectors.latest_episodes,
+        "latest_episodes",
+        &mut diagnostics,
+    );
+    diagnostics.check("latest_episodes", &latest_episodes);
+    let top_upcoming_animes = extract_anime_data(
+        &document,
+        selectors,
+        &selectors.top_upcoming,
+        "top_upcoming_animes",
+        &mut diagnostics,
+    );
+    diagnostics.check("top_upcoming_animes", &top_upcoming_animes);
+    let spotlight_animes =
+        extract_spotlight_anime_data(&document, selectors, &selectors.spotlight, &mut diagnostics);
+    diagnostics.check("spotlight_animes", &spotlight_animes);
+    let genres = extract_genres(&document, &selectors.genres);
+    let top_10_animes = extract_top_10(&document, selectors, &selectors.top_10, &mut diagnostics);
+
+    let (top_airing_animes, most_popular_animes, most_favorite_animes, latest_completed_animes) =
+        extract_featured_anime(&document, selectors, &selectors.featured);
+    diagnostics.check("featured.top_airing_animes", &top_airing_animes);
+    diagnostics.check("featured.most_popular_animes", &most_popular_animes);
+    diagnostics.check("featured.most_favorite_animes", &most_favorite_animes);
+    diagnostics.check("featured.latest_completed_animes", &latest_completed_animes);
+    let featured = FeaturedAnime {
+        top_airing_animes,
+        most_popular_animes,
+        most_favorite_animes,
+        latest_completed_animes,
+    };
+
+    Parsed {
+        data: HomeInfo {
+            trending,
+            latest_episodes,
+            top_upcoming_animes,
+            spotlight_animes,
+            featured,
+            top_10_animes,
+            genres,
+        },
+        diagnostics,
+    }
+}
+
+/// Parses page `page_no` of the A to Z list.
+pub fn parse_atoz(selectors: &Selectors, html: &str, page_no: u32) -> Parsed<AtoZ> {
+    let document = Html::parse_document(html);
+    let mut diagnostics = ParseDiagnostics::default();
+    diagnostics.require(&document, &selectors.atoz);
+
+    let animes = extract_anime_data(
+        &document,
+        selectors,
+        &selectors.atoz,
+        "animes",
+        &mut diagnostics,
+    );
+    diagnostics.check("animes", &animes);
+    let total_pages = get_last_page_no(&document, &selectors.navigation);
+
+    Parsed {
+        data: AtoZ {
+            has_next_page: page_no < total_pages,
+            current_page: page_no,
+            total_pages,
+            animes,
+        },
+        diagnostics,
+    }
+}
+
+/// Parses an anime's page, or returns `None` when it has no details block.
+pub fn parse_about_anime(selectors: &Selectors, html: &str) -> Option<Parsed<AboutAnime>> {
+    let document = Html::parse_document(html);
+    document.select(&selectors.about).next()?;
+
+    let mut diagnostics = ParseDiagnostics::default();
+    for selector in [
+        &selectors.about_info,
+        &selectors.most_popular,
+        &selectors.recommended,
+    ] {
+        diagnostics.require(&document, selector);
+    }
+
+    let about = extract_anime_about_info(&document, selectors, &selectors.about, &mut diagnostics);
+    diagnostics.check("about", std::slice::from_ref(&about));
+    diagnostics.check("most_popular_animes", &about.most_popular_animes);
+    diagnostics.check("related_animes", &about.related_animes);
+    diagnostics.check("recommended_animes", &about.recommended_animes);
+    diagnostics.check("seasons", &about.seasons);
+    check_characters(&mut diagnostics, &about.characters);
+
+    Some(Parsed {
+        data: about,
+        diagnostics,
+    })
+}
+
+/// Parses the qtip AJAX response, the hover card of an anime.
+pub fn parse_anime_qtip(selectors: &Selectors, html: &str) -> Parsed<AnimeQtip> {
+    let document = Html::parse_fragment(html);
+    let mut diagnostics = ParseDiagnostics::default();
+    diagnostics.require(&document, &selectors.qtip);
+
+    let qtip = extract_anime_qtip(&document, selectors, &mut diagnostics);
+    diagnostics.check("qtip", std::slice::from_ref(&qtip));
+
+    Parsed {
+        data: qtip,
+        diagnostics,
+    }
+}
+
+/// Parses page `page_no` of a category.
+pub fn parse_category(selectors: &Selectors, html: &str, page_no: u32) -> Parsed<CategoryInfo> {
+    let document = Html::parse_document(html);
+    let mut diagnostics = ParseDiagnostics::default();
+    for selector in [&selectors.category, &selectors.top_10, &selectors.genres] {
+        diagnostics.require(&document, selector);
+    }
+
+    let animes = extract_anime_data(
+        &document,
+        selectors,
+        &selectors.category,
+        "animes",
+        &mut diagnostics,
+    );
+    diagnostics.check("animes", &animes);
+    let top_10_animes = extract_top_10(&document, selectors, &selectors.top_10, &mut diagnostics);
+    let genres = extract_genres(&document, &selectors.genres);
+    let total_pages = get_last_page_no(&document, &selectors.navigation);
+
+    Parsed {
+        data: CategoryInfo {
+            total_pages,
+            current_page: page_no,
+            has_next_page: page_no < total_pages,
+            animes,
+            top_10_animes,
+            genres,
+        },
+        diagnostics,
+    }
+}
+
+/// Parses page `page_no` of the search results.
+///
+/// A search without results is not a parse failure, so only the sidebar is required.
+pub fn parse_search(selectors: &Selectors, html: &str, page_no: u32) -> Parsed<SearchInfo> {
+    let document = Html::parse_document(html);
+    let mut diagnostics = ParseDiagnostics::default();
+    for selector in [&selectors.search_most_popular, &selectors.genres] {
+        diagnostics.require(&document, selector);
+    }
+
+    let animes = extract_anime_data(
+        &document,
+        selectors,
+        &selectors.search,
+        "animes",
+        &mut diagnostics,
+    );
+    diagnostics.check("animes", &animes);
+    let most_popular_animes = extract_side_bar_animes(
+        &document,
+        selectors,
+        &selectors.search_most_popular,
+        "most_popular_animes",
+        &mut diagnostics,
+    );
+    diagnostics.check("most_popular_animes", &most_popular_animes);
+    let total_pages = get_last_page_no(&document, &selectors.navigation);
+    let genres = extract_genres(&document, &selectors.genres);
+
+    Parsed {
+        data: SearchInfo {
+            total_pages,
+            current_page: page_no,
+            has_next_page: page_no < total_pages,
+            animes,
+            most_popular_animes,
+            genres,
+        },
+        diagnostics,
+    }
+}
+
+/// Parses the `html` field of the search suggestions AJAX response.
+///
+/// Queries without a match get an empty list, so nothing is required.
+pub fn parse_search_suggestions(
+    selectors: &Selectors,
+    html: &str,
+) -> Parsed<Vec<SearchSuggestion>> {
+    let document = Html::parse_fragment(html);
+    let mut diagnostics = ParseDiagnostics::default();
+
+    let suggestions = extract_search_suggestions(&document, selectors);
+    diagnostics.check("suggestions", &suggestions);
+
+    Parsed {
+        data: suggestions,
+        diagnostics,
+    }
+}
+
+/// Parses the `html` field of the schedule AJAX response for `date`, whose
+/// times are local to `tz_offset`.
+///
+/// Days without releases get an empty list, so nothing is required.
+pub fn parse_schedule(
+    selectors: &Selectors,
+    html: &str,
+    date: NaiveDate,
+    tz_offset: FixedOffset,
+) -> Parsed<Vec<ScheduledAnime>> {
+    let document = Html::parse_fragment(html);
+    let mut diagnostics = ParseDiagnostics::default();
+
+    let schedule = extract_schedule(&document, selectors, date, tz_offset, &mut diagnostics);
+    diagnostics.check("schedule", &schedule);
+
+    Parsed {
+        data: schedule,
+        diagnostics,
+    }
+}
+
+/// Parses the next episode's air time from an anime's watch page.
+///
+/// Only airing shows have a countdown, so nothing is required.
+pub fn parse_next_episode(selectors: &Selectors, html: &str) -> Parsed<Option<DateTime<Utc>>> {
+    let document = Html::parse_document(html);
+    let mut diagnostics = ParseDiagnostics::default();
+
+    let airing_at = document
+        .select(&selectors.next_episode)
+        .next()
+        .and_then(|e| e.value().attr("data-value"))
+        .map(str::trim)
+        .and_then(
+            |text| match NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S") {
+                Ok(time) => Some(time.and_utc()),
+                Err(_) => {
+                    diagnostics.invalid("next_episode.airing_at".to_string(), text);
+                    None
+                }
+            },
+        );
+
+    Parsed {
+        data: airing_at,
+        diagnostics,
+    }
+}
+
+/// Parses page `page_no` of the character list AJAX response.
+///
+/// Not every anime has a cast listed, so nothing is required.
+pub fn parse_characters(selectors: &Selectors, html: &str, page_no: u32) -> Parsed<CharactersInfo> {
+    let document = Html::parse_fragment(html);
+    let mut diagnostics = ParseDiagnostics::default();
+
+    let characters = extract_characters(&document, selectors, &selectors.character_list);
+    check_characters(&mut diagnostics, &characters);
+    let total_pages = get_last_page_no(&document, &selectors.character_navigation);
+
+    Parsed {
+        data: CharactersInfo {
+            total_pages,
+            current_page: page_no,
+            has_next_page: page_no < total_pages,
+            characters,
+        },
+        diagnostics,
+    }
+}
+
+/// Parses the `html` field of the episode list AJAX response.
+pub fn parse_episodes(selectors: &Selectors, html: &str) -> Parsed<EpisodesInfo> {
+    let document = Html::parse_document(html);
+    let mut diagnostics = ParseDiagnostics::default();
+    // Upcoming shows have an empty list, so only the list itself is required
+    diagnostics.require(&document, &selectors.episode_list);
+
+    let episodes = extract_anime_episode(&document, &selectors.episodes, &mut diagnostics);
+    diagnostics.check("episodes", &episodes);
+
+    Parsed {
+        data: EpisodesInfo {
+            total_episodes: episodes.len() as u32,
+            episodes,
+        },
+        diagnostics,
+    }
+}
+
+/// Parses the `html` field of the episode servers AJAX response.
+pub fn parse_servers(selectors: &Selectors, html: &str) -> Parsed<ServerInfo> {
+    let document = Html::parse_document(html);
+    let mut diagnostics = ParseDiagnostics::default();
+    for selector in [&selectors.episode_no, &selectors.servers] {
+        diagnostics.require(&document, selector);
+    }
+
+    let episode_str = document
+        .select(&selectors.episode_no)
+        .next()
+        .map(|e| e.text().collect::<String>().trim().to_string())
+        .unwrap_or_default();
+    let last_part = episode_str.split_whitespace().last();
+    let episode_no = diagnostics.number("episode", "episode_no", last_part);
+
+    let sub = extract_episode_servers(
+        &document,
+        selectors,
+        &selectors.sub_servers,
+        "sub",
+        &mut diagnostics,
+    );
+    let dub = extract_episode_servers(
+        &document,
+        selectors,
+        &selectors.dub_servers,
+        "dub",
+        &mut diagnostics,
+    );
+    let raw = extract_episode_servers(
+        &document,
+        selectors,
+        &selectors.raw_servers,
+        "raw",
+        &mut diagnostics,
+    );
+    diagnostics.check("sub", &sub);
+    diagnostics.check("dub", &dub);
+    diagnostics.check("raw", &raw);
+
+    Parsed {
+        data: ServerInfo {
+            episode_no,
+            sub,
+            dub,
+            raw,
+        },
+        diagnostics,
+    }
+}
+
+fn extract_anime_data(
+    document: &Html,
+    selectors: &Selectors,
+    selector: &Selector,
+    section: &str,
+    diagnostics: &mut ParseDiagnostics,
+) -> Vec<Anime> {
+    document
+        .select(selector)
+        .map(|element| {
+            let id = element
+                .select(&selectors.anime_name)
+                .next()
+                .and_then(|e| e.value().attr("href"))
+                .map(|s| s.trim_start_matches('/').to_string())
+                .unwrap_or_default();
+
+            let title = element
+                .select(&selectors.anime_name)
+                .next()
+                .map(|e| e.text().collect::<String>().trim().to_string())
+                .unwrap_or_default();
+
+            let subs = diagnostics.number(
+                section,
+                "subs",
+                element
+                    .select(&selectors.anime_sub)
+                    .next()
+                    .map(|e| e.text().collect::<String>()),
+            );
+
+            let dubs = diagnostics.number(
+                section,
+                "dubs",
+                element
+                    .select(&selectors.anime_dub)
+                    .next()
+                    .map(|e| e.text().collect::<String>()),
+            );
+
+            let eps = diagnostics.number(
+                section,
+                "eps",
+                element
+                    .select(&selectors.anime_eps)
+                    .next()
+                    .map(|e| e.text().collect::<String>()),
+            );
+
+            let duration = element
+                .select(&selectors.anime_duration)
+                .next()
+                .map(|e| e.text().collect::<String>().trim().to_string())
+                .unwrap_or_default();
+
+            let rating = element
+                .select(&selectors.anime_rating)
+                .next()
+                .map(|e| e.text().collect::<String>().trim().to_string())
+                .unwrap_or_default();
+
+            let image = element
+                .select(&selectors.anime_poster)
+                .next()
+                .and_then(|e| e.value().attr("data-src").map(|s| s.to_string()))
+                .unwrap_or_default();
+
+            Anime {
+                id,
+                title,
+                subs,
+                dubs,
+                eps,
+                duration,
+                rating,
+                image,
+            }
+        })
+        .collect()
+}
+
+fn extract_spotlight_anime_data(
+    document: &Html,
+    selectors: &Selectors,
+    selector: &Selector,
+    diagnostics: &mut ParseDiagnostics,
+) -> Vec<SpotlightAnime> {
+    const SECTION: &str = "spotlight_animes";
+
+    document
+        .select(selector)
+        .map(|element| {
+            let id = element
+                .select(&selectors.spotlight_link)
+                .next()
+                .and_then(|e| e.value().attr("href"))
+                .map(|s| s.trim_start_matches('/').to_string())
+                .unwrap_or_default()
+                .split("/")
+                .last()
+                .unwrap_or_default()
+                .to_string();
+
+            let title = element
+                .select(&selectors.spotlight_title)
+                .next()
+                .map(|e| e.text().collect::<String>().trim().to_string())
+                .unwrap_or_default();
+
+            let rank_text = element
+                .select(&selectors.spotlight_rank)
+                .next()
+                .map(|e| e.text().collect::<String>().trim().to_string())
+                .unwrap_or_default();
+            let rank = diagnostics.number(
+                SECTION,
+                "rank",
+                rank_text
+                    .split_whitespace()
+                    .next()
+                    .map(|s| s.trim_start_matches('#')),
+            );
+
+            let image = element
+                .select(&selectors.spotlight_poster)
+                .next()
+                .and_then(|e| e.value().attr("data-src").map(|s| s.to_string()))
+                .unwrap_or_default();
+
+            let description = element
+                .select(&selectors.spotlight_description)
+                .next()
+                .map(|e| e.text().collect::<String>().trim().to_string())
+                .unwrap_or_default();
+
+            let extra_info: Vec<String> = element
+                .select(&selectors.spotlight_details)
+                .map(|e| e.text().collect::<String>().trim().to_string())
+                .collect();
+
+            // The fifth item reads "<subs> <dubs> <eps>"
+            let counts: Vec<&str> = extra_info
+                .get(4)
+                .map(|s| s.split_whitespace().collect())
+                .unwrap_or_default();
+            let subs = diagnostics.number(SECTION, "subs", counts.first());
+            let dubs = diagnostics.number(SECTION, "dubs", counts.get(1));
+            let eps = diagnostics.number(SECTION, "eps", counts.get(2));
+
+            SpotlightAnime {
+                id,
+                title,
+                rank,
+                image,
+                description,
+                subs,
+                dubs,
+                eps,
+                duration: extra_info.get(1).cloned().unwrap_or_default(),
+                quality: extra_info.get(3).cloned().unwrap_or_default(),
+                category: extra_info.first().cloned().unwrap_or_default(),
+                released_day: extra_info.get(2).cloned().unwrap_or_default(),
+            }
+        })
+        .collect()
+}
+
+fn extract_minimal_anime(
+    document: &Html,
+    selectors: &Selectors,
+    selector: &Selector,
+) -> Vec<MinimalAnime> {
+    document
+        .select(selector)
+        .map(|element| {
+            let id = element
+                .select(&selectors.trending_link)
+                .next()
+                .and_then(|e| e.value().attr("href"))
+                .map(|href| href.trim_start_matches('/'))
+                .map(|s| s.to_string())
+                .unwrap_or_default();
+
+            let title = element
+                .select(&selectors.trending_title)
+                .next()
+                .map(|e| e.text().collect::<String>().trim().to_string())
+                .unwrap_or_default();
+
+            let image = element
+                .select(&selectors.trending_poster)
+                .next()
+                .and_then(|e| e.value().attr("data-src"))
+                .map(|s| s.trim().to_string())
+                .unwrap_or_default();
+
+            MinimalAnime { id, title, image }
+        })
+        .collect()
+}
+
+fn extract_featured_anime(
+    document: &Html,
+    selectors: &Selectors,
+    selector: &Selector,
+) -> (
+    Vec<MinimalAnime>,
+    Vec<MinimalAnime>,
+    Vec<MinimalAnime>,
+    Vec<MinimalAnime>,
+) {
+    let res: Vec<MinimalAnime> = document
+        .select(selector)
+        .map(|element| {
+            let id = element
+                .select(&selectors.featured_name)
+                .next()
+                .and_then(|e| e.value().attr("href"))
+                .map(|href| href.trim_start_matches('/').to_string())
+                .unwrap_or_default();
+
+            let title = element
+                .select(&selectors.featured_name)
+                .next()
+                .map(|e| e.text().collect::<String>().trim().to_string())
+                .unwrap_or_default();
+
+            let image = element
+                .select(&selectors.featured_poster)
+                .next()
+                .and_then(|e| e.value().attr("data-src"))
+                .map(|s| s.trim().to_string())
+                .unwrap_or_default();
+
+            MinimalAnime { id, title, image }
+        })
+        .collect();
+
+    // Four blocks of five; pages with fewer entries leave the last blocks short or empty
+    let mut blocks = res.chunks(5).map(<[MinimalAnime]>::to_vec);
+    let top_airing_animes = blocks.next().unwrap_or_default();
+    let most_popular_animes = blocks.next().unwrap_or_default();
+    let most_favorite_animes = blocks.next().unwrap_or_default();
+    let latest_completed_animes = blocks.next().unwrap_or_default();
+
+    (
+        top_airing_animes,
+        most_popular_animes,
+        most_favorite_animes,
+        latest_completed_animes,
+    )
+}
+
+fn extract_top_10(
+    document: &Html,
+    selectors: &Selectors,
+    selector: &Selector,
+    diagnostics: &mut ParseDiagnostics,
+) -> Top10PeriodRankedAnime {
+    let (day, week, month) = document
+        .select(selector)
+        .filter_map(|element| element.value().attr("id"))
+        .map(|id| id.split('-').next_back().unwrap_or("").trim().to_string())
+        .fold(
+            (vec![], vec![], vec![]),
+            |(mut day, mut week, mut month), period_type| {
+                match period_type.as_str() {
+                    "week" => week.extend(extract_top_10_by_period_type(
+                        document,
+                        selectors,
+                        "week",
+                        diagnostics,
+                    )),
+                    "month" => month.extend(extract_top_10_by_period_type(
+                        document,
+                        selectors,
+                        "month",
+                        diagnostics,
+                    )),
+                    _ => day.extend(extract_top_10_by_period_type(
+                        document,
+                        selectors,
+                        "day",
+                        diagnostics,
+                    )),
+                }
+                (day, week, month)
+            },
+        );
+
+    diagnostics.check("top_10_animes.day", &day);
+    diagnostics.check("top_10_animes.week", &week);
+    diagnostics.check("top_10_animes.month", &month);
+
+    Top10PeriodRankedAnime { day, week, month }
+}
+
+fn extract_top_10_by_period_type(
+    document: &Html,
+    selectors: &Selectors,
+    period_type: &str,
+    diagnostics: &mut ParseDiagnostics,
+) -> Vec<Top10Anime> {
+    let selector = match period_type {
+        "week" => &selectors.top_10_week,
+        "month" => &selectors.top_10_month,
+        _ => &selectors.top_10_day,
+    };
+    let section = format!("top_10_animes.{}", period_type);
+
+    document
+        .select(selector)
+        .map(|element| {
+            let id = element
+                .select(&selectors.top_10_name)
+                .next()
+                .and_then(|e| e.value().attr("href"))
+                .map(|s| s.trim_start_matches('/').to_string())
+                .unwrap_or_default();
+
+            let title = element
+                .select(&selectors.top_10_name)
+                .next()
+                .map(|e| e.text().collect::<String>().trim().to_string())
+                .unwrap_or_default();
+
+            let rank = diagnostics.number(
+                &section,
+                "rank",
+                element
+                    .select(&selectors.top_10_rank)
+                    .next()
+                    .map(|e| e.text().collect::<String>()),
+            );
+
+            let subs = diagnostics.number(
+                &section,
+                "subs",
+                element
+                    .select(&selectors.top_10_sub)
+                    .next()
+                    .map(|e| e.text().collect::<String>()),
+            );
+
+            let dubs = diagnostics.number(
+                &section,
+                "dubs",
+                element
+                    .select(&selectors.top_10_dub)
+                    .next()
+                    .map(|e| e.text().collect::<String>()),
+            );
+
+            let eps = diagnostics.number(
+                &section,
+                "eps",
+                element
+                    .select(&selectors.top_10_eps)
+                    .next()
+                    .map(|e| e.text().collect::<String>()),
+            );
+
+            let image = element
+                .select(&selectors.top_10_poster)
+                .next()
+                .and_then(|e| e.value().attr("data-src").map(|s| s.to_string()))
+                .unwrap_or_default();
+
+            Top10Anime {
+                id,
+                title,
+                image,
+                subs,
+                dubs,
+                eps,
+                rank,
+            }
+        })
+        .collect()
+}
+
+fn extract_anime_about_info(
+    document: &Html,
+    selectors: &Selectors,
+    selector: &Selector,
+    diagnostics: &mut ParseDiagnostics,
+) -> AboutAnime {
+    const SECTION: &str = "about";
+
+    let mut about_anime = AboutAnime {
+        id: String::new(),
+        mal_id: 0,
+        anime_id: 0,
+        al_id: 0,
+        title: String::new(),
+        description: String::new(),
+        image: String::new(),
+        category: String::new(),
+        rating: String::new(),
+        quality: String::new(),
+        duration: String::new(),
+        subs: 0,
+        dubs: 0,
+        eps: 0,
+        japanese: String::new(),
+        synonyms: String::new(),
+        aired: String::new(),
+        premiered: String::new(),
+        status: String::new(),
+        mal_score: String::new(),
+        studios: vec![],
+        producers: vec![],
+        genres: vec![],
+        most_popular_animes: vec![],
+        related_animes: vec![],
+        recommended_animes: vec![],
+        seasons: vec![],
+        characters: vec![],
+    };
+
+    document.select(selector).for_each(|element| {
+        about_anime.id = element
+            .select(&selectors.about_play_button)
+            .next()
+            .and_then(|e| e.value().attr("href"))
+            .map(|s| s.split('/').next_back().unwrap_or("").to_string())
+            .unwrap_or_default();
+
+        about_anime.title = element
+            .select(&selectors.about_name)
+            .next()
+            .map(|e| e.text().collect::<String>().trim().to_string())
+            .unwrap_or_default();
+
+        about_anime.rating = element
+            .select(&selectors.about_rating)
+            .next()
+            .map(|e| e.text().collect::<String>().trim().to_string())
+            .unwrap_or_default();
+
+        about_anime.quality = element
+            .select(&selectors.about_quality)
+            .next()
+            .map(|e| e.text().collect::<String>().trim().to_string())
+            .unwrap_or_default();
+
+        about_anime.subs = diagnostics.number(
+            SECTION,
+            "subs",
+            element
+                .select(&selectors.about_sub)
+                .next()
+                .map(|e| e.text().collect::<String>()),
+        );
+
+        about_anime.dubs = diagnostics.number(
+            SECTION,
+            "dubs",
+            element
+                .select(&selectors.about_dub)
+                .next()
+                .map(|e| e.text().collect::<String>()),
+        );
+
+        about_anime.eps = diagnostics.number(
+            SECTION,
+            "eps",
+            element
+                .select(&selectors.about_eps)
+                .next()
+                .map(|e| e.text().collect::<String>()),
+        );
+
+        about_anime.image = element
+            .select(&selectors.about_poster)
+            .next()
+            .and_then(|e| e.value().attr("src").map(|s| s.to_string()))
+            .unwrap_or_default();
+
+        about_anime.description = element
+            .select(&selectors.about_description)
+            .next()
+            .map(|e| e.text().collect::<String>().trim().to_string())
+            .unwrap_or_default();
+
+        if let Some(tick) = element.select(&selectors.about_stats).next() {
+            let text = tick
+                .text()
+                .collect::<String>()
+                .replace('\n', " ")
+                .trim()
+                .to_string();
+
+            // The tick ends with "<category> <duration>", e.g. "... TV 24m"
+            let mut parts = text.split_whitespace().rev();
+            about_anime.duration = parts.next().unwrap_or("").to_string();
+            about_anime.category = parts.next().unwrap_or("").to_string();
+        }
+
+        let json_text = document
+            .select(&selectors.about_sync_data)
+            .next()
+            .map(|script| script.text().collect::<String>())
+            .unwrap_or_default();
+
+        if let Ok(json) = serde_json::from_str::<Value>(&json_text) {
+            about_anime.anime_id = diagnostics.number(
+                SECTION,
+                "anime_id",
+                json.get("anime_id").and_then(Value::as_str),
+            );
+            about_anime.mal_id = diagnostics.number(
+                SECTION,
+                "mal_id",
+                json.get("mal_id").and_then(Value::as_str),
+            );
+            about_anime.al_id = diagnostics.number(
+                SECTION,
+                "al_id",
+                json.get("anilist_id").and_then(Value::as_str),
+            );
+        }
+    });
+
+    document.select(&selectors.about_info).for_each(|element| {
+        let head = element
+            .select(&selectors.about_info_head)
+            .next()
+            .map(|e| e.text().collect::<String>().trim().to_string())
+            .unwrap_or_default();
+
+        let key = element
+            .select(&selectors.about_info_value)
+            .next()
+            .map(|e| e.text().collect::<String>().trim().to_string())
+            .unwrap_or_default();
+
+        match head.as_str() {
+            "Japanese:" => about_anime.japanese = key,
+            "Synonyms:" => about_anime.synonyms = key,
+            "Aired:" => about_anime.aired = key,
+            "Premiered:" => about_anime.premiered = key,
+            "Status:" => about_anime.status = key,
+            "MAL Score:" => about_anime.mal_score = key,
+            "Producers:" => {
+                about_anime.producers.extend(
+                    element
+                        .select(&selectors.about_info_links)
+                        .map(|e| e.text().collect::<String>().trim().to_string()),
+                );
+            }
+            "Studios:" => {
+                about_anime.studios.extend(
+                    element
+                        .select(&selectors.about_info_links)
+                        .map(|e| e.text().collect::<String>().trim().to_string()),
+                );
+            }
+            _ => {}
+        }
+    });
+
+    document
+        .select(&selectors.about_genres)
+        .for_each(|element| {
+            about_anime.genres.extend(
+                element
+                    .select(&selectors.about_genre_links)
+                    .map(|e| e.text().collect::<String>().trim().to_string()),
+            );
+        });
+
+    about_anime
+        .most_popular_animes
+        .extend(extract_side_bar_animes(
+            document,
+            selectors,
+            &selectors.most_popular,
+            "most_popular_animes",
+            diagnostics,
+        ));
+    about_anime.related_animes.extend(extract_side_bar_animes(
+        document,
+        selectors,
+        &selectors.related,
+        "related_animes",
+        diagnostics,
+    ));
+    about_anime.recommended_animes.extend(extract_anime_data(
+        document,
+        selectors,
+        &selectors.recommended,
+        "recommended_animes",
+        diagnostics,
+    ));
+    about_anime.seasons.extend(extract_anime_seasons(
+        document,
+        selectors,
+        &selectors.seasons,
+    ));
+    about_anime.characters.extend(extract_characters(
+        document,
+        selectors,
+        &selectors.characters,
+    ));
+
+    about_anime
+}
+
+fn extract_anime_qtip(
+    document: &Html,
+    selectors: &Selectors,
+    diagnostics: &mut ParseDiagnostics,
+) -> AnimeQtip {
+    const SECTION: &str = "qtip";
+
+    let mut qtip = AnimeQtip {
+        id: String::new(),
+        title: String::new(),
+        description: String::new(),
+        mal_score: String::new(),
+        quality: String::new(),
+        category: String::new(),
+        subs: 0,
+        dubs: 0,
+        eps: 0,
+        japanese: String::new(),
+        synonyms: String::new(),
+        aired: String::new(),
+        status: String::new(),
+        genres: vec![],
+    };
+
+    let Some(element) = document.select(&selectors.qtip).next() else {
+        return qtip;
+    };
+    let text_of = |selector: &Selector| {
+        element
+            .select(selector)
+            .next()
+            .map(|e| e.text().collect::<String>().trim().to_string())
+    };
+
+    qtip.id = element
+        .select(&selectors.qtip_watch)
+        .next()
+        .and_then(|e| e.value().attr("href"))
+        .and_then(|href| href.split('/').next_back())
+        .unwrap_or_default()
+        .to_string();
+    qtip.title = text_of(&selectors.qtip_title).unwrap_or_default();
+    qtip.description = text_of(&selectors.qtip_description).unwrap_or_default();
+    qtip.mal_score = text_of(&selectors.qtip_score).unwrap_or_default();
+    qtip.quality = text_of(&selectors.qtip_quality).unwrap_or_default();
+    qtip.category = text_of(&selectors.qtip_category).unwrap_or_default();
+    qtip.subs = diagnostics.number(SECTION, "subs", text_of(&selectors.qtip_sub));
+    qtip.dubs = diagnostics.number(SECTION, "dubs", text_of(&selectors.qtip_dub));
+    qtip.eps = diagnostics.number(SECTION, "eps", text_of(&selectors.qtip_eps));
+
+    for line in element.select(&selectors.qtip_line) {
+        let head = line
+            .select(&selectors.qtip_line_head)
+            .next()
+            .map(|e| e.text().collect::<String>().trim().to_string())
+            .unwrap_or_default();
+
+        let value = line
+            .select(&selectors.qtip_line_value)
+            .next()
+            .map(|e| e.text().collect::<String>().trim().to_string())
+            .unwrap_or_default();
+
+        match head.as_str() {
+            "Japanese:" => qtip.japanese = value,
+            "Synonyms:" => qtip.synonyms = value,
+            "Aired:" => qtip.aired = value,
+            "Status:" => qtip.status = value,
+            "Genres:" => qtip.genres.extend(
+                line.select(&selectors.qtip_line_links)
+                    .map(|e| e.text().collect::<String>().trim().to_string()),
+            ),
+            _ => {}
+        }
+    }
+
+    qtip
+}
+
+fn extract_side_bar_animes(
+    document: &Html,
+    selectors: &Selectors,
+    selector: &Selector,
+    section: &str,
+    diagnostics: &mut ParseDiagnostics,
+) -> Vec<SideBarAnimes> {
+    document
+        .select(selector)
+        .map(|element| {
+            let id = element
+                .select(&selectors.sidebar_name)
+                .next()
+                .and_then(|e| e.value().attr("href"))
+                .map(|s| s.trim_start_matches('/').to_string())
+                .unwrap_or_default();
+
+            let title = element
+                .select(&selectors.sidebar_name)
+                .next()
+                .map(|e| e.text().collect::<String>().trim().to_string())
+                .unwrap_or_default();
+
+            let image = element
+                .select(&selectors.sidebar_poster)
+                .next()
+                .and_then(|e| e.value().attr("data-src").map(|s| s.to_string()))
+                .unwrap_or_default();
+
+            let subs = diagnostics.number(
+                section,
+                "subs",
+                element
+                    .select(&selectors.sidebar_sub)
+                    .next()
+                    .map(|e| e.text().collect::<String>()),
+            );
+
+            let dubs = diagnostics.number(
+                section,
+                "dubs",
+                element
+                    .select(&selectors.sidebar_dub)
+                    .next()
+                    .map(|e| e.text().collect::<String>()),
+            );
+
+            let eps = diagnostics.number(
+                section,
+                "eps",
+                element
+                    .select(&selectors.sidebar_eps)
+                    .next()
+                    .map(|e| e.text().collect::<String>()),
+            );
+
+            let category = element
+                .select(&selectors.sidebar_tick)
+                .next()
+                .map(|e| e.text().collect::<String>().trim().to_string())
+                .map(|s| s.replace('\n', " ").replace("  ", " ").trim().to_string())
+                .map(|s| s.split_whitespace().last().unwrap_or_default().to_string())
+                .unwrap_or_default();
+
+            SideBarAnimes {
+                id,
+                title,
+                image,
+                subs,
+                dubs,
+                eps,
+                category,
+            }
+        })
+        .collect()
+}
+
+fn extract_search_suggestions(document: &Html, selectors: &Selectors) -> Vec<SearchSuggestion> {
+    document
+        .select(&selectors.suggestions)
+        .map(|element| {
+            let id = element
+                .value()
+                .attr("href")
+                .map(|s| s.trim_start_matches('/'))
+                .and_then(|s| s.split('?').next())
+                .unwrap_or_default()
+                .to_string();
+
+            let name = element.select(&selectors.suggestion_name).next();
+            let title = name
+                .map(|e| e.text().collect::<String>().trim().to_string())
+                .unwrap_or_default();
+            let japanese = name
+                .and_then(|e| e.value().attr("data-jname"))
+                .map(|s| s.trim().to_string())
+                .unwrap_or_default();
+
+            let image = element
+                .select(&selectors.suggestion_poster)
+                .next()
+                .and_then(|e| e.value().attr("data-src"))
+                .map(|s| s.to_string())
+                .unwrap_or_default();
+
+            // Upcoming shows have no release date, so count from the end
+            let info: Vec<String> = element
+                .select(&selectors.suggestion_info)
+                .next()
+                .map(|e| {
+                    e.text()
+                        .map(|text| text.trim().to_string())
+                        .filter(|text| !text.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default();
+            let from_end = |n: usize| {
+                info.len()
+                    .checked_sub(n)
+                    .map(|i| info[i].clone())
+                    .unwrap_or_default()
+            };
+
+            SearchSuggestion {
+                id,
+                title,
+                japanese,
+                image,
+                aired: from_end(3),
+                category: from_end(2),
+                duration: from_end(1),
+            }
+        })
+        .collect()
+}
+
+fn extract_schedule(
+    document: &Html,
+    selectors: &Selectors,
+    date: NaiveDate,
+    tz_offset: FixedOffset,
+    diagnostics: &mut ParseDiagnostics,
+) -> Vec<ScheduledAnime> {
+    document
+        .select(&selectors.schedule)
+        .map(|element| {
+            let id = element
+                .value()
+                .attr("href")
+                .map(|s| s.trim_start_matches('/').to_string())
+                .unwrap_or_default();
+
+            let name = element.select(&selectors.schedule_name).next();
+            let title = name
+                .map(|e| e.text().collect::<String>().trim().to_string())
+                .unwrap_or_default();
+            let japanese = name
+                .and_then(|e| e.value().attr("data-jname"))
+                .map(|s| s.trim().to_string())
+                .unwrap_or_default();
+
+            let time_text = element
+                .select(&selectors.schedule_time)
+                .next()
+                .map(|e| e.text().collect::<String>().trim().to_string())
+                .unwrap_or_default();
+            let airing_at = airing_at(date, &time_text, tz_offset, diagnostics);
+
+            let episode_no = diagnostics.number(
+                "schedule",
+                "episode_no",
+                element
+                    .select(&selectors.schedule_episode)
+                    .next()
+                    .and_then(|e| {
+                        e.text()
+                            .collect::<String>()
+                            .split_whitespace()
+                            .last()
+                            .map(String::from)
+                    }),
+            );
+
+            ScheduledAnime {
+                id,
+                title,
+                japanese,
+                airing_at,
+                episode_no,
+            }
+        })
+        .collect()
+}
+
+// Combines `date` with an `HH:MM` time in `tz_offset`; an unreadable time is
+// recorded and falls back to midnight
+fn airing_at(
+    date: NaiveDate,
+    time: &str,
+    tz_offset: FixedOffset,
+    diagnostics: &mut ParseDiagnostics,
+) -> DateTime<FixedOffset> {
+    let time = NaiveTime::parse_from_str(time, "%H:%M").unwrap_or_else(|_| {
+        diagnostics.invalid("schedule.airing_at".to_string(), time);
+        NaiveTime::MIN
+    });
+
+    // Fixed offsets have no gaps or overlaps, so the local time always maps
+    tz_offset
+        .from_local_datetime(&date.and_time(time))
+        .single()
+        .unwrap()
+}
+
+fn extract_characters(
+    document: &Html,
+    selectors: &Selectors,
+    selector: &Selector,
+) -> Vec<Character> {
+    document
+        .select(selector)
+        .map(|element| {
+            let (id, name, role, image) = element
+                .select(&selectors.character_info)
+                .next()
+                .map(|e| extract_person(e, selectors))
+                .unwrap_or_default();
+
+            let voice_actors = element
+                .select(&selectors.voice_actor_info)
+                .map(|e| {
+                    let (id, name, language, image) = extract_person(e, selectors);
+                    VoiceActor {
+                        id,
+                        name,
+                        language,
+                        image,
+                    }
+                })
+                .collect();
+
+            Character {
+                id,
+                name,
+                role,
+                image,
+                voice_actors,
+            }
+        })
+        .collect()
+}
+
+// Reads the id, name, detail line and image of a character or voice actor card;
+// the detail line is the character's role or the voice actor's language
+fn extract_person(
+    element: scraper::ElementRef,
+    selectors: &Selectors,
+) -> (String, String, String, String) {
+    let link = element.select(&selectors.person_name).next();
+    let id = link
+        .and_then(|e| e.value().attr("href"))
+        .and_then(|href| href.trim_end_matches('/').split('/').next_back())
+        .unwrap_or_default()
+        .to_string();
+    let name = link
+        .map(|e| e.text().collect::<String>().trim().to_string())
+        .unwrap_or_default();
+
+    let detail = element
+        .select(&selectors.person_detail)
+        .next()
+        .map(|e| e.text().collect::<String>().trim().to_string())
+        .unwrap_or_default();
+
+    let image = element
+        .select(&selectors.person_image)
+        .next()
+        .and_then(|e| e.value().attr("data-src"))
+        .map(|s| s.to_string())
+        .unwrap_or_default();
+
+    (id, name, detail, image)
+}
+
+fn check_characters(diagnostics: &mut ParseDiagnostics, characters: &[Character]) {
+    diagnostics.check("characters", characters);
+    for character in characters {
+        diagnostics.check("characters.voice_actors", &character.voice_actors);
+    }
+}
+
+fn extract_anime_seasons(
+    document: &Html,
+    selectors: &Selectors,
+    selector: &Selector,
+) -> Vec<AnimeSeason> {
+    document
+        .select(selector)
+        .map(|element| {
+            let id = element
+                .value()
+                .attr("href")
+                .map(|s| s.trim_start_matches('/').to_string())
+                .unwrap_or_default();
+
+            let title = element
+                .value()
+                .attr("title")
+                .map(|e| e.trim().to_string())
+                .unwrap_or_default();
+
+            let anime_title = element
+                .select(&selectors.season_title)
+                .next()
+                .map(|e| e.text().collect::<String>().trim().to_string())
+                .unwrap_or_default();
+
+            let mut image = element
+                .select(&selectors.season_poster)
+                .next()
+                .and_then(|e| e.value().attr("style").map(|s| s.to_string()))
+                .unwrap_or_default();
+
+            let re = Regex::new(r"url\((?P<url>.*?)\)").unwrap();
+
+            image = re
+                .captures(&image)
+                .and_then(|caps| caps.name("url"))
+                .map(|m| m.as_str().trim_matches('"').to_string())
+                .unwrap_or_default();
+
+            let is_current = element.has_class("active");
+
+            AnimeSeason {
+                id,
+                title,
+                anime_title,
+                image,
+                is_current,
+            }
+        })
+        .collect()
+}
+
+fn extract_anime_episode(
+    document: &Html,
+    selector: &Selector,
+    diagnostics: &mut ParseDiagnostics,
+) -> Vec<AnimeEpisode> {
+    document
+        .select(selector)
+        .filter_map(|element| {
+            let id = element
+                .value()
+                .attr("href")
+                .map(|s| s.trim_start_matches('/').to_string())
+                .unwrap_or_default()
+                .split('/')
+                .next_back()
+                .unwrap_or_default()
+                .to_string();
+
+            if id.is_empty() {
+                return None;
+            }
+
+            let title = element
+                .value()
+                .attr("title")
+                .map(|e| e.trim().to_string())
+                .unwrap_or_default();
+
+            let episode_no = diagnostics.number(
+                "episodes",
+                "episode_no",
+                element.value().attr("data-number"),
+            );
+
+            let is_filler = element.has_class("ssl-item-filler");
+
+            Some(AnimeEpisode {
+                id,
+                title,
+                episode_no,
+                is_filler,
+            })
+        })
+        .collect()
+}
+
+fn extract_episode_servers(
+    document: &Html,
+    selectors: &Selectors,
+    selector: &Selector,
+    section: &str,
+    diagnostics: &mut ParseDiagnostics,
+) -> Vec<Server> {
+    document
+        .select(selector)
+        .map(|element| {
+            let server_name = element
+                .select(&selectors.server_name)
+                .next()
+                .map(|e| {
+                    e.text()
+                        .collect::<String>()
+                        .to_lowercase()
+                        .trim()
+                        .to_string()
+                })
+                .unwrap_or_default();
+
+            let data_id = diagnostics.number(section, "data_id", element.attr("data-id"));
+
+            let server_id =
+                diagnostics.number(section, "server_id", element.attr("data-server-id"));
+
+            Server {
+                server_name,
+                server_id,
+                data_id,
+            }
+        })
+        .collect()
+}
+
+fn extract_genres(document: &Html, selector: &Selector) -> Vec<String> {
+    document
+        .select(selector)
+        .map(|element| {
+            let text = element.text().collect::<String>().trim().to_string();
+            if text.is_empty() {
+                String::new()
+            } else {
+                text
+            }
+        })
+        .collect()
+}
+
+// Function to extract the last page number from the response
+fn get_last_page_no(document: &Html, selector: &Selector) -> u32 {
+    document
+        .select(selector)
+        .next_back()
+        .and_then(|element| element.value().attr("href"))
+        .and_then(|href| href.split('=').next_back())
+        .and_then(|page_str| page_str.parse::<u32>().ok())
+        .unwrap_or(1)
+}
@@ -1,59 +1,556 @@
-use rand::seq::SliceRandom;
-use reqwest::Client;
+use std::collections::HashSet;
+use std::fmt;
+use std::net::Ipv6Addr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use crate::{env::EnvVar, error::AniRustError};
+use rand::{distributions::WeightedIndex, prelude::Distribution, seq::SliceRandom};
+use reqwest::{header::HeaderMap, Client, Url};
+use tokio::{sync::Semaphore, task::JoinSet};
+
+use crate::{
+    error::AniRustError,
+    transport::{FetchRequest, Fetcher},
+};
+
+// Max number of proxies probed at the same time during validation
+const VALIDATION_CONCURRENCY: usize = 64;
+
+/// Protocol spoken by a proxy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProxyKind {
+    Http,
+    Https,
+    Socks4,
+    Socks5,
+}
+
+impl ProxyKind {
+    pub fn from_scheme(scheme: &str) -> Option<Self> {
+        match scheme.to_ascii_lowercase().as_str() {
+            "http" => Some(ProxyKind::Http),
+            "https" => Some(ProxyKind::Https),
+            "socks4" | "socks4a" => Some(ProxyKind::Socks4),
+            "socks5" | "socks5h" => Some(ProxyKind::Socks5),
+            _ => None,
+        }
+    }
+
+    pub fn scheme(&self) -> &str {
+        match self {
+            ProxyKind::Http => "http",
+            ProxyKind::Https => "https",
+            ProxyKind::Socks4 => "socks4",
+            ProxyKind::Socks5 => "socks5",
+        }
+    }
+}
+
+/// Username and password used to authenticate against a proxy
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProxyCredentials {
+    pub username: String,
+    pub password: String,
+}
 
 // Define a struct to hold proxy data
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Proxy {
+    pub kind: ProxyKind,
+    /// `host:port` of the proxy
     pub address: String,
+    pub credentials: Option<ProxyCredentials>,
 }
 
-// Function to get a random proxy from the list
-pub fn get_random_proxy(proxies: &[Proxy]) -> Option<Proxy> {
-    proxies.choose(&mut rand::thread_rng()).cloned()
-}
+impl Proxy {
+    pub fn new(kind: ProxyKind, address: &str) -> Self {
+        Proxy {
+            kind,
+            address: address.to_string(),
+            credentials: None,
+        }
+    }
 
-// Fetch proxy list from URL
-pub async fn fetch_proxy_list(url: &str) -> Result<Vec<Proxy>, AniRustError> {
-    let client = Client::new();
-    let response = client.get(url).send().await?.text().await?;
-    let proxies = response
-        .lines()
-        .filter_map(|line| {
-            let line = line.trim();
-            if !line.is_empty() {
-                Some(Proxy {
-                    address: line.to_string(),
-                })
-            } else {
-                None
+    /// Parses a single line of a proxy list.
+    ///
+    /// Accepts `ip:port`, `ip:port:user:pass`, `user:pass@host:port` and
+    /// `scheme://[user:pass@]host:port`, where the host may be a bracketed IPv6
+    /// address such as `[::1]`. Lines without a scheme get `default_kind`.
+    pub fn parse(line: &str, default_kind: ProxyKind) -> Option<Self> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (kind, rest) = match line.split_once("://") {
+            Some((scheme, rest)) => (ProxyKind::from_scheme(scheme)?, rest),
+            None => (default_kind, line),
+        };
+
+        let (host, port, credentials) = match rest.rsplit_once('@') {
+            Some((auth, address)) => {
+                let (username, password) = auth.split_once(':').unwrap_or((auth, ""));
+                let (host, port, None) = split_address(address)? else {
+                    return None;
+                };
+                (host, port, Some(credentials(username, password)))
             }
-        })
-        .collect();
-    Ok(proxies)
+            None => match split_address(rest)? {
+                (host, port, Some(auth)) => {
+                    let (username, password) = auth.split_once(':')?;
+                    (host, port, Some(credentials(username, password)))
+                }
+                (host, port, None) => (host, port, None),
+            },
+        };
+
+        let proxy = Proxy {
+            kind,
+            address: format!("{}:{}", host, port),
+            credentials,
+        };
+        // Drop hosts and credentials that would only fail once the proxy is used
+        proxy.url().ok()?;
+        Some(proxy)
+    }
+
+    /// Full proxy URL, including credentials when present
+    pub fn url(&self) -> Result<Url, AniRustError> {
+        let mut url = Url::parse(&format!("{}://{}", self.kind.scheme(), self.address))
+            .map_err(|e| AniRustError::UnknownError(format!("Invalid proxy {}: {}", self, e)))?;
+
+        if let Some(credentials) = &self.credentials {
+            let invalid = |_| AniRustError::UnknownError(format!("Invalid proxy {}", self));
+            url.set_username(&credentials.username).map_err(invalid)?;
+            url.set_password(Some(&credentials.password))
+                .map_err(invalid)?;
+        }
+
+        Ok(url)
+    }
+
+    /// Builds the matching `reqwest` proxy, routing all traffic through it
+    pub fn to_reqwest(&self) -> Result<reqwest::Proxy, AniRustError> {
+        Ok(reqwest::Proxy::all(self.url()?)?)
+    }
+}
+
+impl fmt::Display for Proxy {
+    // Credentials are left out so proxies can be logged safely
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}://{}", self.kind.scheme(), self.address)
+    }
 }
 
-// Load proxies from multiple sources
-pub async fn load_proxies() -> Result<Vec<Proxy>, AniRustError> {
-    let sock5_url = EnvVar::SOCK5_URL.get_config();
-    let sock4_url = EnvVar::SOCK4_URL.get_config();
-    let http_url = EnvVar::HTTP_URL.get_config();
+fn credentials(username: &str, password: &str) -> ProxyCredentials {
+    ProxyCredentials {
+        username: username.to_string(),
+        password: password.to_string(),
+    }
+}
+
+// Splits `host:port[:rest]` into its host, port and rest. IPv6 hosts must be
+// bracketed, since their colons would otherwise be taken for the port separator
+fn split_address(address: &str) -> Option<(&str, u16, Option<&str>)> {
+    let (host, after_host) = match address.strip_prefix('[') {
+        Some(bracketed) => {
+            let (ip, after_host) = bracketed.split_once(']')?;
+            ip.parse::<Ipv6Addr>().ok()?;
+            (&address[..ip.len() + 2], after_host.strip_prefix(':')?)
+        }
+        None => address.split_once(':')?,
+    };
+    if host.is_empty() {
+        return None;
+    }
+
+    let (port, rest) = match after_host.split_once(':') {
+        Some((port, rest)) => (port, Some(rest)),
+        None => (after_host.trim_end_matches('/'), None),
+    };
+    Some((host, port.parse().ok()?, rest))
+}
+
+/// How [`ProxyPool`] picks the proxy for the next request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProxySelection {
+    /// Uniformly random, ignoring health
+    Random,
+    /// Random, weighted by success rate and latency
+    #[default]
+    Weighted,
+    /// The proxy with the fewest recorded failures
+    LeastFailures,
+}
+
+/// Tuning knobs for [`ProxyPool`]
+#[derive(Debug, Clone)]
+pub struct ProxyPoolConfig {
+    pub selection: ProxySelection,
+    /// Consecutive failures after which a proxy is quarantined
+    pub max_consecutive_failures: u32,
+    /// How long a quarantined proxy is skipped
+    pub quarantine_duration: Duration,
+    /// Number of quarantines after which a proxy is evicted from the pool
+    pub max_quarantines: u32,
+}
+
+impl Default for ProxyPoolConfig {
+    fn default() -> Self {
+        ProxyPoolConfig {
+            selection: ProxySelection::default(),
+            max_consecutive_failures: 3,
+            quarantine_duration: Duration::from_secs(300),
+            max_quarantines: 3,
+        }
+    }
+}
+
+/// Health record kept for every proxy in a [`ProxyPool`]
+#[derive(Debug, Clone, Default)]
+pub struct ProxyStats {
+    pub successes: u32,
+    pub failures: u32,
+    pub consecutive_failures: u32,
+    pub quarantines: u32,
+    /// Moving average of successful request latency
+    pub avg_latency: Option<Duration>,
+    pub quarantined_until: Option<Instant>,
+}
+
+impl ProxyStats {
+    fn is_quarantined(&self, now: Instant) -> bool {
+        self.quarantined_until.is_some_and(|until| until > now)
+    }
+
+    // Laplace-smoothed success rate, scaled down for slow proxies
+    fn weight(&self) -> f64 {
+        let success_rate =
+            (self.successes as f64 + 1.0) / (self.successes as f64 + self.failures as f64 + 2.0);
+        let latency = self.avg_latency.map(|l| l.as_secs_f64()).unwrap_or(1.0);
+        success_rate / (1.0 + latency)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ProxyEntry {
+    proxy: Proxy,
+    stats: ProxyStats,
+}
+
+/// Set of proxies that tracks the health of each one and prefers the healthy ones.
+///
+/// Proxies that keep failing are quarantined for a while and evicted once they
+/// have been quarantined too often. While every proxy is quarantined, requests
+/// still go through the one released soonest rather than failing.
+#[derive(Debug)]
+pub struct ProxyPool {
+    entries: Mutex<Vec<ProxyEntry>>,
+    sources: Vec<ProxySource>,
+    config: ProxyPoolConfig,
+    // Reused for every list download
+    client: Client,
+}
+
+impl ProxyPool {
+    pub fn new(proxies: Vec<Proxy>) -> Self {
+        Self::with_config(proxies, ProxyPoolConfig::default())
+    }
+
+    pub fn with_config(proxies: Vec<Proxy>, config: ProxyPoolConfig) -> Self {
+        let entries = proxies
+            .into_iter()
+            .map(|proxy| ProxyEntry {
+                proxy,
+                stats: ProxyStats::default(),
+            })
+            .collect();
+
+        ProxyPool {
+            entries: Mutex::new(entries),
+            sources: Vec::new(),
+            config,
+            client: Client::new(),
+        }
+    }
+
+    /// Empty pool that fills itself from `sources` on every [`ProxyPool::refresh`]
+    pub fn from_sources(sources: Vec<ProxySource>, config: ProxyPoolConfig) -> Self {
+        ProxyPool {
+            entries: Mutex::new(Vec::new()),
+            sources,
+            config,
+            client: Client::new(),
+        }
+    }
+
+    pub fn sources(&self) -> &[ProxySource] {
+        &self.sources
+    }
+
+    /// Reloads every source and swaps the new list in.
+    ///
+    /// Sources that fail are skipped; the current list is only kept when all of
+    /// them fail. Returns the number of proxies in the pool afterwards.
+    pub async fn refresh(&self) -> Result<usize, AniRustError> {
+        if self.sources.is_empty() {
+            return Ok(self.len());
+        }
+
+        let mut proxies = Vec::new();
+        let mut last_error = None;
+        let mut loaded_any = false;
+
+        for source in &self.sources {
+            match source.load(&self.client).await {
+                Ok(list) => {
+                    loaded_any = true;
+                    proxies.extend(list);
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        if !loaded_any {
+            if let Some(e) = last_error {
+                return Err(e);
+            }
+        }
+
+        self.replace(proxies);
+        Ok(self.len())
+    }
+
+    /// Atomically swaps the proxy list, keeping the stats of proxies still present.
+    ///
+    /// Requests already running keep using the proxy they picked.
+    pub fn replace(&self, proxies: Vec<Proxy>) {
+        let mut entries = self.entries.lock().unwrap();
+        let mut seen = HashSet::new();
+
+        let new_entries = proxies
+            .into_iter()
+            .filter(|proxy| seen.insert(proxy.clone()))
+            .map(|proxy| {
+                let stats = entries
+                    .iter()
+                    .find(|entry| entry.proxy == proxy)
+                    .map(|entry| entry.stats.clone())
+                    .unwrap_or_default();
+                ProxyEntry { proxy, stats }
+            })
+            .collect();
+
+        *entries = new_entries;
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Snapshot of every proxy in the pool along with its stats
+    pub fn stats(&self) -> Vec<(Proxy, ProxyStats)> {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|entry| (entry.proxy.clone(), entry.stats.clone()))
+            .collect()
+    }
+
+    /// Picks the proxy for the next request, skipping quarantined ones.
+    ///
+    /// When every proxy is quarantined, the one released soonest is returned.
+    pub fn select(&self) -> Option<Proxy> {
+        let entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+        let available: Vec<&ProxyEntry> = entries
+            .iter()
+            .filter(|entry| !entry.stats.is_quarantined(now))
+            .collect();
+
+        if available.is_empty() {
+            return entries
+                .iter()
+                .min_by_key(|entry| entry.stats.quarantined_until)
+                .map(|entry| entry.proxy.clone());
+        }
+
+        let mut rng = rand::thread_rng();
+        let picked = match self.config.selection {
+            ProxySelection::Random => available.choose(&mut rng).copied(),
+            ProxySelection::Weighted => {
+                match WeightedIndex::new(available.iter().map(|entry| entry.stats.weight())) {
+                    Ok(index) => available.get(index.sample(&mut rng)).copied(),
+                    Err(_) => available.choose(&mut rng).copied(),
+                }
+            }
+            ProxySelection::LeastFailures => {
+                let least = available.iter().map(|e| e.stats.failures).min();
+                let candidates: Vec<&ProxyEntry> = available
+                    .iter()
+                    .filter(|entry| Some(entry.stats.failures) == least)
+                    .copied()
+                    .collect();
+                candidates.choose(&mut rng).copied()
+            }
+        };
+
+        picked.map(|entry| entry.proxy.clone())
+    }
 
-    let mut all_proxies = Vec::new();
+    pub fn record_success(&self, proxy: &Proxy, latency: Duration) {
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(entry) = entries.iter_mut().find(|entry| &entry.proxy == proxy) {
+            let stats = &mut entry.stats;
+            stats.successes += 1;
+            stats.consecutive_failures = 0;
+            stats.quarantined_until = None;
+            stats.avg_latency = Some(match stats.avg_latency {
+                Some(avg) => avg.mul_f64(0.8) + latency.mul_f64(0.2),
+                None => latency,
+            });
+        }
+    }
+
+    pub fn record_failure(&self, proxy: &Proxy) {
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(entry) = entries.iter_mut().find(|entry| &entry.proxy == proxy) {
+            entry.stats.failures += 1;
+            entry.stats.consecutive_failures += 1;
+            if entry.stats.consecutive_failures >= self.config.max_consecutive_failures {
+                self.quarantine_entry(entry);
+            }
+        }
+        self.evict(&mut entries);
+    }
+
+    /// Takes a proxy out of rotation for the configured quarantine duration
+    pub fn quarantine(&self, proxy: &Proxy) {
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(entry) = entries.iter_mut().find(|entry| &entry.proxy == proxy) {
+            self.quarantine_entry(entry);
+        }
+        self.evict(&mut entries);
+    }
+
+    fn quarantine_entry(&self, entry: &mut ProxyEntry) {
+        entry.stats.consecutive_failures = 0;
+        entry.stats.quarantines += 1;
+        entry.stats.quarantined_until = Some(Instant::now() + self.config.quarantine_duration);
+    }
+
+    fn evict(&self, entries: &mut Vec<ProxyEntry>) {
+        entries.retain(|entry| entry.stats.quarantines < self.config.max_quarantines);
+    }
+
+    /// Probes every proxy against `probe_url` and quarantines the ones that fail.
+    ///
+    /// Returns the number of healthy proxies.
+    pub async fn validate(
+        &self,
+        fetcher: Arc<dyn Fetcher>,
+        probe_url: &str,
+        timeout: Duration,
+    ) -> usize {
+        let semaphore = Arc::new(Semaphore::new(VALIDATION_CONCURRENCY));
+        let mut probes = JoinSet::new();
+
+        for (proxy, _) in self.stats() {
+            let fetcher = fetcher.clone();
+            let semaphore = semaphore.clone();
+            let request = FetchRequest {
+                url: probe_url.to_string(),
+                headers: HeaderMap::new(),
+                proxy: Some(proxy.clone()),
+                timeout,
+            };
+
+            probes.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                let start = Instant::now();
+                let healthy = matches!(
+                    fetcher.fetch(request).await,
+                    Ok(response) if response.status.is_success()
+                );
+                (proxy, healthy, start.elapsed())
+            });
+        }
+
+        let mut healthy_count = 0;
+        while let Some(result) = probes.join_next().await {
+            if let Ok((proxy, healthy, latency)) = result {
+                if healthy {
+                    healthy_count += 1;
+                    self.record_success(&proxy, latency);
+                } else {
+                    self.quarantine(&proxy);
+                }
+            }
+        }
 
-    if !sock5_url.is_empty() {
-        let sock5_proxies = fetch_proxy_list(&sock5_url).await?;
-        all_proxies.extend(sock5_proxies);
+        healthy_count
     }
-    if !sock4_url.is_empty() {
-        let sock4_proxies = fetch_proxy_list(&sock4_url).await?;
-        all_proxies.extend(sock4_proxies);
+}
+
+/// Where a proxy list is loaded from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProxySource {
+    Url { url: String, kind: ProxyKind },
+    File { path: PathBuf, kind: ProxyKind },
+}
+
+impl ProxySource {
+    /// Treats `http(s)://` locations as URLs and anything else as a local file path.
+    pub fn from_location(location: &str, kind: ProxyKind) -> Self {
+        let location = location.trim();
+        if location.starts_with("http://") || location.starts_with("https://") {
+            ProxySource::Url {
+                url: location.to_string(),
+                kind,
+            }
+        } else {
+            ProxySource::File {
+                path: PathBuf::from(location.trim_start_matches("file://")),
+                kind,
+            }
+        }
     }
-    if !http_url.is_empty() {
-        let http_proxies = fetch_proxy_list(&http_url).await?;
-        all_proxies.extend(http_proxies);
+
+    pub async fn load(&self, client: &Client) -> Result<Vec<Proxy>, AniRustError> {
+        match self {
+            ProxySource::Url { url, kind } => fetch_proxy_list(client, url, *kind).await,
+            ProxySource::File { path, kind } => read_proxy_file(path, *kind).await,
+        }
     }
+}
 
-    Ok(all_proxies)
+// Fetch proxy list from URL
+pub async fn fetch_proxy_list(
+    client: &Client,
+    url: &str,
+    kind: ProxyKind,
+) -> Result<Vec<Proxy>, AniRustError> {
+    let response = client.get(url).send().await?.text().await?;
+    Ok(parse_proxy_list(&response, kind))
+}
+
+// Read proxy list from a local file
+pub async fn read_proxy_file(path: &Path, kind: ProxyKind) -> Result<Vec<Proxy>, AniRustError> {
+    let content = tokio::fs::read_to_string(path).await.map_err(|e| {
+        AniRustError::UnknownError(format!("Failed to read {}: {}", path.display(), e))
+    })?;
+    Ok(parse_proxy_list(&content, kind))
+}
+
+fn parse_proxy_list(content: &str, kind: ProxyKind) -> Vec<Proxy> {
+    content
+        .lines()
+        .filter_map(|line| Proxy::parse(line, kind))
+        .collect()
 }
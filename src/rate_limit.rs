@@ -0,0 +1,204 @@
+// rate_limit.rs
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::Url;
+use tokio::{
+    sync::{OwnedSemaphorePermit, Semaphore},
+    time::Instant,
+};
+
+use crate::error::AniRustError;
+
+/// Throttle applied to every request sent to one host
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimit {
+    /// Rate at which the token bucket refills
+    pub requests_per_second: f64,
+    /// Requests that may be sent back to back once the bucket is full
+    pub burst: u32,
+    /// Requests to the host that may be in flight at the same time
+    pub max_in_flight: usize,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        RateLimit {
+            requests_per_second: 10.0,
+            burst: 10,
+            max_in_flight: 16,
+        }
+    }
+}
+
+impl RateLimit {
+    fn validate(&self) -> Result<(), AniRustError> {
+        if !self.requests_per_second.is_finite() || self.requests_per_second <= 0.0 {
+            return Err(AniRustError::InvalidConfig(
+                "requests_per_second must be greater than zero".to_string(),
+            ));
+        }
+        if self.burst == 0 {
+            return Err(AniRustError::InvalidConfig(
+                "burst must be at least 1".to_string(),
+            ));
+        }
+        if self.max_in_flight == 0 {
+            return Err(AniRustError::InvalidConfig(
+                "max_in_flight must be at least 1".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Rate limits keyed by host, such as the HiAnime domains, `megacloud.tv` or
+/// the StreamTape hosts
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    /// Limit for hosts without an entry in `hosts`; `None` leaves them unthrottled
+    pub default: Option<RateLimit>,
+    pub hosts: HashMap<String, RateLimit>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig::new(RateLimit::default())
+    }
+}
+
+impl RateLimitConfig {
+    /// Throttles every host with `limit`
+    pub fn new(limit: RateLimit) -> Self {
+        RateLimitConfig {
+            default: Some(limit),
+            hosts: HashMap::new(),
+        }
+    }
+
+    /// No throttling at all
+    pub fn unlimited() -> Self {
+        RateLimitConfig {
+            default: None,
+            hosts: HashMap::new(),
+        }
+    }
+
+    pub fn host(mut self, host: &str, limit: RateLimit) -> Self {
+        self.hosts.insert(host.to_ascii_lowercase(), limit);
+        self
+    }
+
+    fn limit_for(&self, host: &str) -> Option<&RateLimit> {
+        self.hosts.get(host).or(self.default.as_ref())
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), AniRustError> {
+        self.default
+            .iter()
+            .chain(self.hosts.values())
+            .try_for_each(RateLimit::validate)
+    }
+}
+
+// Token bucket and in-flight cap of a single host
+#[derive(Debug)]
+struct HostLimiter {
+    limit: RateLimit,
+    bucket: Mutex<Bucket>,
+    in_flight: Arc<Semaphore>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl HostLimiter {
+    fn new(limit: RateLimit) -> Self {
+        HostLimiter {
+            bucket: Mutex::new(Bucket {
+                tokens: limit.burst as f64,
+                refilled_at: Instant::now(),
+            }),
+            in_flight: Arc::new(Semaphore::new(limit.max_in_flight)),
+            limit,
+        }
+    }
+
+    // Takes a token, or returns how long to wait until one is available
+    fn try_take(&self) -> Result<(), Duration> {
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+        let refill =
+            now.duration_since(bucket.refilled_at).as_secs_f64() * self.limit.requests_per_second;
+        bucket.tokens = (bucket.tokens + refill).min(self.limit.burst as f64);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let missing = 1.0 - bucket.tokens;
+            Err(Duration::from_secs_f64(
+                missing / self.limit.requests_per_second,
+            ))
+        }
+    }
+}
+
+/// Applies a [`RateLimitConfig`] to outgoing requests.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    hosts: Mutex<HashMap<String, Arc<HostLimiter>>>,
+}
+
+/// Slot for one request; the host's in-flight count drops when it is dropped
+#[derive(Debug)]
+pub struct RatePermit {
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        RateLimiter {
+            config,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    /// Waits until a request to `url` may be sent under the host's limit.
+    pub async fn acquire(&self, url: &str) -> RatePermit {
+        let Some(host) = self.host_limiter(url) else {
+            return RatePermit { _permit: None };
+        };
+
+        // Wait for a free slot first so queued requests do not burn tokens
+        let permit = host.in_flight.clone().acquire_owned().await.ok();
+        while let Err(wait) = host.try_take() {
+            tokio::time::sleep(wait).await;
+        }
+
+        RatePermit { _permit: permit }
+    }
+
+    fn host_limiter(&self, url: &str) -> Option<Arc<HostLimiter>> {
+        let url = Url::parse(url).ok()?;
+        let host = url.host_str()?.to_ascii_lowercase();
+        let limit = self.config.limit_for(&host)?;
+
+        let mut hosts = self.hosts.lock().unwrap();
+        let limiter = hosts
+            .entry(host)
+            .or_insert_with(|| Arc::new(HostLimiter::new(limit.clone())));
+        Some(limiter.clone())
+    }
+}
@@ -0,0 +1,224 @@
+// replay.rs
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    StatusCode, Url,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::AniRustError,
+    transport::{FetchRequest, FetchResponse, Fetcher},
+};
+
+/// A recorded request/response pair, stored as one JSON file per request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fixture {
+    pub url: String,
+    pub request_headers: Vec<(String, String)>,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Fixture {
+    fn new(request: &FetchRequest, response: &FetchResponse) -> Self {
+        Fixture {
+            url: request.url.clone(),
+            request_headers: header_pairs(&request.headers),
+            status: response.status.as_u16(),
+            headers: header_pairs(&response.headers),
+            body: response.body.clone(),
+        }
+    }
+
+    fn to_response(&self) -> Result<FetchResponse, AniRustError> {
+        let status =
+            StatusCode::from_u16(self.status).map_err(|e| AniRustError::InvalidResponse {
+                url: self.url.clone(),
+                reason: format!("Invalid recorded status: {}", e),
+            })?;
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                headers.append(name, value);
+            }
+        }
+
+        Ok(FetchResponse {
+            status,
+            headers,
+            body: self.body.clone(),
+        })
+    }
+
+    /// File name of the fixture for `url` inside a fixtures directory
+    pub fn file_name(url: &str) -> String {
+        format!("{:x}.json", md5::compute(url))
+    }
+}
+
+/// [`Fetcher`] that forwards to another one and saves every response to a
+/// fixtures directory, to be served back later by [`ReplayFetcher`].
+#[derive(Debug)]
+pub struct RecordingFetcher {
+    inner: Arc<dyn Fetcher>,
+    dir: PathBuf,
+}
+
+impl RecordingFetcher {
+    /// Records the responses of `inner` into `dir`, creating it if needed.
+    pub fn new(inner: Arc<dyn Fetcher>, dir: impl Into<PathBuf>) -> Result<Self, AniRustError> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).map_err(|e| fixtures_error(&dir, e))?;
+        Ok(RecordingFetcher { inner, dir })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+#[async_trait]
+impl Fetcher for RecordingFetcher {
+    async fn fetch(&self, request: FetchRequest) -> Result<FetchResponse, AniRustError> {
+        let response = self.inner.fetch(request.clone()).await?;
+
+        let fixture = Fixture::new(&request, &response);
+        let json = serde_json::to_string_pretty(&fixture)
+            .map_err(|e| AniRustError::UnknownError(format!("Failed to encode fixture: {}", e)))?;
+        let path = self.dir.join(Fixture::file_name(&request.url));
+        tokio::fs::write(&path, json)
+            .await
+            .map_err(|e| fixtures_error(&path, e))?;
+
+        Ok(response)
+    }
+}
+
+/// [`Fetcher`] that serves the fixtures saved by [`RecordingFetcher`] and never
+/// touches the network.
+///
+/// Requests are matched on their exact URL, ignoring only the cache-busting
+/// parameters in [`IGNORED_PARAMS`], such as the timestamp appended to the
+/// MegaCloud player script. A request without a fixture fails with
+/// [`AniRustError::NoRecordedResponse`].
+#[derive(Debug, Clone)]
+pub struct ReplayFetcher {
+    fixtures: BTreeMap<String, Fixture>,
+}
+
+/// Query parameters that change on every request and are ignored when matching
+pub const IGNORED_PARAMS: [&str; 1] = ["v"];
+
+impl ReplayFetcher {
+    /// Loads every fixture in `dir`.
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self, AniRustError> {
+        let dir = dir.as_ref();
+        let entries = std::fs::read_dir(dir).map_err(|e| fixtures_error(dir, e))?;
+
+        let mut fixtures = Vec::new();
+        for entry in entries {
+            let path = entry.map_err(|e| fixtures_error(dir, e))?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                let content =
+                    std::fs::read_to_string(&path).map_err(|e| fixtures_error(&path, e))?;
+                let fixture = serde_json::from_str(&content).map_err(|e| {
+                    AniRustError::InvalidConfig(format!(
+                        "Invalid fixture {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?;
+                fixtures.push(fixture);
+            }
+        }
+
+        Ok(Self::from_fixtures(fixtures))
+    }
+
+    /// Fixtures that only differ by an ignored parameter are deduplicated,
+    /// keeping the one whose URL sorts last.
+    pub fn from_fixtures(mut fixtures: Vec<Fixture>) -> Self {
+        fixtures.sort_by(|a, b| a.url.cmp(&b.url));
+        ReplayFetcher {
+            fixtures: fixtures
+                .into_iter()
+                .map(|fixture| (match_key(&fixture.url), fixture))
+                .collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.fixtures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fixtures.is_empty()
+    }
+
+    fn find(&self, url: &str) -> Option<&Fixture> {
+        self.fixtures.get(&match_key(url))
+    }
+}
+
+#[async_trait]
+impl Fetcher for ReplayFetcher {
+    async fn fetch(&self, request: FetchRequest) -> Result<FetchResponse, AniRustError> {
+        match self.find(&request.url) {
+            Some(fixture) => fixture.to_response(),
+            None => Err(AniRustError::NoRecordedResponse(request.url)),
+        }
+    }
+}
+
+fn header_pairs(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.as_str().to_string(),
+                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            )
+        })
+        .collect()
+}
+
+// `url` without its ignored parameters; other URLs are kept byte for byte so
+// that their encoding does not change
+fn match_key(url: &str) -> String {
+    let Ok(mut parsed) = Url::parse(url) else {
+        return url.to_string();
+    };
+    if !parsed
+        .query_pairs()
+        .any(|(name, _)| IGNORED_PARAMS.contains(&name.as_ref()))
+    {
+        return url.to_string();
+    }
+
+    let kept: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(name, _)| !IGNORED_PARAMS.contains(&name.as_ref()))
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+    if kept.is_empty() {
+        parsed.set_query(None);
+    } else {
+        parsed.query_pairs_mut().clear().extend_pairs(kept);
+    }
+    parsed.into()
+}
+
+fn fixtures_error(path: &Path, e: std::io::Error) -> AniRustError {
+    AniRustError::InvalidConfig(format!("Fixtures at {}: {}", path.display(), e))
+}
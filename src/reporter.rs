@@ -0,0 +1,85 @@
+// reporter.rs
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde_json::json;
+
+use crate::{config::Webhooks, error::AniRustError};
+
+/// Receives the errors returned by the public `HiAnimeRust` API, failed proxy
+/// refreshes, and incomplete parses when the client uses
+/// [`ParseMode::Report`](crate::parse::ParseMode::Report).
+///
+/// Errors that are recovered from internally, such as a failed attempt that is
+/// retried on another proxy or domain, are never reported.
+pub trait ErrorReporter: fmt::Debug + Send + Sync {
+    fn report(&self, error: &AniRustError);
+}
+
+/// Posts errors to the webhook configured for their kind.
+///
+/// Requests are sent in the background on the current tokio runtime; outside a
+/// runtime the error is dropped silently.
+#[derive(Debug, Clone, Default)]
+pub struct WebhookReporter {
+    webhooks: Webhooks,
+    client: reqwest::Client,
+}
+
+impl WebhookReporter {
+    pub fn new(webhooks: Webhooks) -> Self {
+        WebhookReporter {
+            webhooks,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub fn webhooks(&self) -> &Webhooks {
+        &self.webhooks
+    }
+}
+
+impl ErrorReporter for WebhookReporter {
+    fn report(&self, error: &AniRustError) {
+        let Some(webhook_url) = error.webhook_url(&self.webhooks) else {
+            return;
+        };
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+
+        let now: DateTime<Utc> = Utc::now();
+        let timestamp = now.format("%Y-%m-%d %H:%M:%S").to_string();
+        let content = json!({
+            "Timestamp": timestamp,
+            "Error": error.to_string(),
+        });
+        let payload = json!({
+            "content": content.to_string(),
+        });
+
+        let request = self.client.post(webhook_url.clone()).json(&payload);
+        runtime.spawn(async move {
+            let _res = request.send().await;
+        });
+    }
+}
+
+/// Writes errors to stderr.
+#[derive(Debug, Clone, Default)]
+pub struct LogReporter;
+
+impl ErrorReporter for LogReporter {
+    fn report(&self, error: &AniRustError) {
+        eprintln!("aniscraper error: {}", error);
+    }
+}
+
+/// Ignores every error.
+#[derive(Debug, Clone, Default)]
+pub struct NoopReporter;
+
+impl ErrorReporter for NoopReporter {
+    fn report(&self, _error: &AniRustError) {}
+}
@@ -0,0 +1,94 @@
+// retry.rs
+
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::error::AniRustError;
+
+/// How a request is retried when it fails.
+///
+/// The delay before attempt `n + 1` is `base_delay * 2^(n - 1)`, capped at
+/// `max_delay`, with up to `jitter` of it removed at random so concurrent
+/// requests do not retry in lockstep.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Attempts made before giving up, including the first one
+    pub max_attempts: usize,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Fraction of each delay that is randomised, from 0.0 to 1.0
+    pub jitter: f64,
+    /// Time budget for all attempts together, if any
+    pub deadline: Option<Duration>,
+    /// Timeout of a single attempt
+    pub attempt_timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 50,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(10),
+            jitter: 0.5,
+            // Without it, 50 attempts at up to 15s each could hold a call for minutes
+            deadline: Some(Duration::from_secs(60)),
+            attempt_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy that makes a single attempt.
+    pub fn no_retries() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Delay to wait after the failed attempt number `attempt`, starting at 1.
+    pub fn delay(&self, attempt: usize) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31) as u32;
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(exponent))
+            .min(self.max_delay);
+
+        if self.jitter <= 0.0 || delay.is_zero() {
+            return delay;
+        }
+        let max_jitter = delay.mul_f64(self.jitter.min(1.0));
+        delay - max_jitter.mul_f64(rand::thread_rng().gen::<f64>())
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), AniRustError> {
+        if self.max_attempts == 0 {
+            return Err(AniRustError::InvalidConfig(
+                "max_attempts must be at least 1".to_string(),
+            ));
+        }
+        if self.attempt_timeout.is_zero() {
+            return Err(AniRustError::InvalidConfig(
+                "attempt_timeout must be greater than zero".to_string(),
+            ));
+        }
+        if self.base_delay > self.max_delay {
+            return Err(AniRustError::InvalidConfig(
+                "base_delay must not exceed max_delay".to_string(),
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.jitter) {
+            return Err(AniRustError::InvalidConfig(
+                "jitter must be between 0.0 and 1.0".to_string(),
+            ));
+        }
+        if self.deadline.is_some_and(|deadline| deadline.is_zero()) {
+            return Err(AniRustError::InvalidConfig(
+                "deadline must be greater than zero".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,295 @@
+// selectors.rs
+
+use std::path::Path;
+use std::sync::Arc;
+
+use lazy_static::lazy_static;
+use scraper::Selector;
+use serde::{Deserialize, Serialize};
+
+use crate::error::AniRustError;
+
+// Declares every selector once, with its default, and derives the serializable
+// `SelectorProfile` and the compiled `Selectors` from that list
+macro_rules! selector_profile {
+    ($($(#[doc = $doc:literal])* $field:ident: $default:literal,)*) => {
+        /// CSS selectors used to parse HiAnime pages.
+        ///
+        /// Every field has a default matching the current markup, so a profile
+        /// loaded from a file only needs the selectors it overrides. Page-level
+        /// selectors are matched against the whole document, the others against
+        /// a single item of the enclosing list.
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+        #[serde(default, deny_unknown_fields)]
+        pub struct SelectorProfile {
+            $($(#[doc = $doc])* pub $field: String,)*
+        }
+
+        impl Default for SelectorProfile {
+            fn default() -> Self {
+                SelectorProfile {
+                    $($field: $default.to_string(),)*
+                }
+            }
+        }
+
+        /// A [`SelectorProfile`] with every selector compiled.
+        #[derive(Debug, Clone)]
+        pub struct Selectors {
+            profile: SelectorProfile,
+            $(pub(crate) $field: Selector,)*
+        }
+
+        impl Selectors {
+            /// Compiles `profile`, failing on the first invalid selector.
+            pub fn new(profile: SelectorProfile) -> Result<Self, AniRustError> {
+                Ok(Selectors {
+                    $($field: compile(stringify!($field), &profile.$field)?,)*
+                    profile,
+                })
+            }
+        }
+    };
+}
+
+selector_profile! {
+    /// Home: slides of the trending carousel
+    trending: "#anime-trending #trending-home .swiper-wrapper .swiper-slide",
+    /// Home: items of the latest episodes list
+    latest_episodes: "#main-content .block_area_home:nth-of-type(1) .tab-content .film_list-wrap .flw-item",
+    /// Home: items of the top upcoming list
+    top_upcoming: "#main-content .block_area_home:nth-of-type(3) .tab-content .film_list-wrap .flw-item",
+    /// Home: slides of the spotlight carousel
+    spotlight: "#slider .swiper-wrapper .swiper-slide",
+    /// Home: items of the four featured blocks, five per block
+    featured: "#anime-featured .row div:nth-of-type(1) .anif-block-ul ul li",
+    /// Sidebar genre links
+    genres: "#main-sidebar .block_area.block_area_sidebar.block_area-genres .sb-genre-list li",
+    /// Top 10 blocks, whose id ends with `day`, `week` or `month`
+    top_10: "#main-sidebar .block_area-realtime [id^=\"top-viewed-\"]",
+    top_10_day: "#top-viewed-day ul li",
+    top_10_week: "#top-viewed-week ul li",
+    top_10_month: "#top-viewed-month ul li",
+    /// Items of the A to Z list
+    atoz: "#main-wrapper div div.page-az-wrap section div.tab-content div div.film_list-wrap .flw-item",
+    /// Link to the last page of a paginated list
+    navigation: "div.pre-pagination.mt-5.mb-5 > nav > ul > li:last-child a",
+    /// Items of a category page
+    category: "#main-content .tab-content .film_list-wrap .flw-item",
+    /// Items of the search results
+    search: "#main-content .tab-content .film_list-wrap .flw-item",
+    /// Sidebar of the search results
+    search_most_popular: "#main-sidebar .block_area.block_area_sidebar.block_area-realtime .anif-block-ul ul li",
+    /// Details block of an anime's page
+    about: "#ani_detail .ani_detail-stage .container .anis-content",
+    /// Rows such as "Aired:" or "Status:" in the details block
+    about_info: "#ani_detail .ani_detail-stage .container .anis-content .anisc-info .item-title",
+    /// Genre row of the details block
+    about_genres: "#ani_detail .ani_detail-stage .container .anis-content .anisc-info .item-list",
+    /// Script holding the anime's ids as JSON
+    about_sync_data: "#syncData",
+    /// Sidebar of an anime's page
+    most_popular: "#main-sidebar .block_area.block_area_sidebar.block_area-realtime:nth-of-type(2) .anif-block-ul ul li",
+    related: "#main-sidebar .block_area.block_area_sidebar.block_area-realtime:nth-of-type(1) .anif-block-ul ul li",
+    recommended: "#main-content .block_area.block_area_category .tab-content .flw-item",
+    /// Cast of an anime's page
+    characters: "#main-content .block_area-actors .bac-list-wrap .bac-item",
+    /// Characters of the character list AJAX response
+    character_list: ".bac-list-wrap .bac-item",
+    /// Link to the last page of the character list AJAX response
+    character_navigation: ".pre-pagination nav .pagination li:last-child a",
+    /// Links to the other seasons of an anime
+    seasons: ".os-list a.os-item",
+    /// Items of the search suggestions AJAX response, without the "View all" link
+    suggestions: "a.nav-item:not(.nav-bottom)",
+    /// Items of the schedule AJAX response
+    schedule: ".tsl-link",
+    /// Watch page: element whose `data-value` is the next episode's UTC air time
+    next_episode: ".schedule-alert .alert.small span[data-value]",
+    /// Hover card of the qtip AJAX response
+    qtip: ".pre-qtip-content",
+    /// Episode list of the episodes AJAX response
+    episode_list: ".detail-infor-content .ss-list",
+    /// Episode links of the episodes AJAX response
+    episodes: ".detail-infor-content .ss-list a",
+    /// Notice naming the episode in the servers AJAX response
+    episode_no: ".server-notice strong",
+    /// Every server of the servers AJAX response
+    servers: ".ps_-block .ps__-list .server-item",
+    sub_servers: ".ps_-block.ps_-block-sub.servers-sub .ps__-list .server-item",
+    dub_servers: ".ps_-block.ps_-block-sub.servers-dub .ps__-list .server-item",
+    raw_servers: ".ps_-block.ps_-block-sub.servers-raw .ps__-list .server-item",
+
+    /// Anime card: link whose text is the title
+    anime_name: ".film-name .dynamic-name",
+    anime_sub: ".film-poster .tick-sub",
+    anime_dub: ".film-poster .tick-dub",
+    anime_eps: ".film-poster .tick-eps",
+    anime_duration: ".fd-infor .fdi-duration",
+    anime_rating: ".film-poster .tick-rate",
+    anime_poster: ".film-poster .film-poster-img",
+
+    /// Spotlight slide: links to the anime, the first one is used
+    spotlight_link: ".deslide-item-content .desi-buttons a",
+    spotlight_title: ".deslide-item-content .desi-head-title.dynamic-name",
+    spotlight_rank: ".deslide-item-content .desi-sub-text",
+    spotlight_poster: ".deslide-cover .deslide-cover-img .film-poster-img",
+    spotlight_description: ".deslide-item-content .desi-description",
+    /// Spotlight slide: category, duration, release day, quality and counts, in that order
+    spotlight_details: ".deslide-item-content .sc-detail .scd-item",
+
+    /// Trending slide: poster link to the anime
+    trending_link: ".item .film-poster",
+    trending_title: ".item .number .film-title.dynamic-name",
+    trending_poster: ".item .film-poster .film-poster-img",
+
+    /// Featured item: link whose text is the title
+    featured_name: ".film-detail .film-name .dynamic-name",
+    featured_poster: ".film-poster a .film-poster-img",
+
+    /// Top 10 item: link whose text is the title
+    top_10_name: ".film-detail .film-name .dynamic-name",
+    top_10_rank: ".film-number span",
+    top_10_sub: ".film-detail .fd-infor .tick-item.tick-sub",
+    top_10_dub: ".film-detail .fd-infor .tick-item.tick-dub",
+    top_10_eps: ".film-detail .fd-infor .tick-item.tick-eps",
+    top_10_poster: ".film-poster .film-poster-img",
+
+    /// Details block: link to the first episode
+    about_play_button: ".anisc-detail .film-buttons a.btn-play",
+    about_name: ".anisc-detail .film-name.dynamic-name",
+    about_rating: ".film-stats .tick .tick-pg",
+    about_quality: ".film-stats .tick .tick-quality",
+    about_sub: ".film-stats .tick .tick-sub",
+    about_dub: ".film-stats .tick .tick-dub",
+    about_eps: ".film-stats .tick .tick-eps",
+    about_poster: ".anisc-poster .film-poster .film-poster-img",
+    about_description: ".anisc-detail .film-description .text",
+    /// Details block: stats ending with the category and duration
+    about_stats: ".film-stats .tick",
+    /// Details row: label such as "Aired:"
+    about_info_head: ".item-head",
+    about_info_value: ".name",
+    /// Details row: links of multi-valued rows such as "Producers:"
+    about_info_links: "a.name",
+    about_genre_links: "a",
+
+    /// Sidebar item: link whose text is the title
+    sidebar_name: ".film-detail .dynamic-name",
+    /// Sidebar item: ticks ending with the category
+    sidebar_tick: ".fd-infor .tick",
+    sidebar_sub: ".fd-infor .tick .tick-item.tick-sub",
+    sidebar_dub: ".fd-infor .tick .tick-item.tick-dub",
+    sidebar_eps: ".fd-infor .tick .tick-item.tick-eps",
+    sidebar_poster: ".film-poster .film-poster-img",
+
+    /// Suggestion: title, with the Japanese title in `data-jname`
+    suggestion_name: ".srp-detail .film-name",
+    suggestion_poster: ".film-poster .film-poster-img",
+    /// Suggestion: release date, category and duration, in that order
+    suggestion_info: ".srp-detail .film-infor",
+
+    /// Schedule item: local air time as `HH:MM`
+    schedule_time: ".time",
+    /// Schedule item: title, with the Japanese title in `data-jname`
+    schedule_name: ".film-detail .film-name",
+    /// Schedule item: button text ending with the episode number
+    schedule_episode: ".film-detail .fd-play button",
+
+    /// Season link: season name
+    season_title: ".title",
+    /// Season link: element whose inline style holds the poster URL
+    season_poster: ".season-poster",
+
+    qtip_title: ".pre-qtip-title",
+    qtip_score: ".pre-qtip-detail .pqd-li",
+    qtip_quality: ".pre-qtip-detail .tick .tick-quality",
+    qtip_sub: ".pre-qtip-detail .tick .tick-sub",
+    qtip_dub: ".pre-qtip-detail .tick .tick-dub",
+    qtip_eps: ".pre-qtip-detail .tick .tick-eps",
+    qtip_category: ".pre-qtip-detail .badge-quality",
+    qtip_description: ".pre-qtip-description",
+    /// Hover card: rows such as "Aired:" or "Genres:"
+    qtip_line: ".pre-qtip-line",
+    qtip_line_head: ".stick",
+    qtip_line_value: ".stick-text",
+    qtip_line_links: "a",
+    /// Hover card: link to the watch page, whose last segment is the anime's id
+    qtip_watch: ".pre-qtip-button a.btn-play",
+
+    /// Cast item: card of the character
+    character_info: ".per-info.ltr",
+    /// Cast item: cards of the character's voice actors
+    voice_actor_info: ".per-info.rtl",
+    /// Character or voice actor card: link whose text is the name
+    person_name: ".pi-detail .pi-name a",
+    /// Character or voice actor card: role or language
+    person_detail: ".pi-detail .pi-cast",
+    person_image: ".pi-avatar img",
+
+    /// Server item: link whose text is the server name
+    server_name: "a",
+}
+
+lazy_static! {
+    static ref DEFAULT_SELECTORS: Arc<Selectors> =
+        Arc::new(Selectors::new(SelectorProfile::default()).unwrap());
+}
+
+impl SelectorProfile {
+    pub fn from_json(json: &str) -> Result<Self, AniRustError> {
+        serde_json::from_str(json).map_err(|e| {
+            AniRustError::InvalidConfig(format!("Invalid JSON selector profile: {}", e))
+        })
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Self, AniRustError> {
+        toml::from_str(toml).map_err(|e| {
+            AniRustError::InvalidConfig(format!("Invalid TOML selector profile: {}", e))
+        })
+    }
+
+    /// Loads a `.json` or `.toml` profile, picking the format from the extension.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, AniRustError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            AniRustError::InvalidConfig(format!(
+                "Failed to read selector profile {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Self::from_json(&content),
+            Some("toml") => Self::from_toml(&content),
+            _ => Err(AniRustError::InvalidConfig(format!(
+                "Selector profile {} must be a .json or .toml file",
+                path.display()
+            ))),
+        }
+    }
+}
+
+impl Selectors {
+    /// Selectors of the built-in profile, compiled once per process.
+    pub fn shared_default() -> Arc<Selectors> {
+        DEFAULT_SELECTORS.clone()
+    }
+
+    pub fn profile(&self) -> &SelectorProfile {
+        &self.profile
+    }
+}
+
+impl Default for Selectors {
+    fn default() -> Self {
+        Selectors::clone(&DEFAULT_SELECTORS)
+    }
+}
+
+fn compile(field: &str, css: &str) -> Result<Selector, AniRustError> {
+    Selector::parse(css).map_err(|e| {
+        AniRustError::InvalidConfig(format!("Invalid selector for {}: `{}` ({})", field, css, e))
+    })
+}
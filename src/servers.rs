@@ -1,3 +1,4 @@
+use base64::{engine::general_purpose::STANDARD, Engine as _};
 use regex::Regex;
 use scraper::Html;
 use serde::{Deserialize, Serialize};
@@ -5,9 +6,11 @@ use serde_json::Value;
 
 use crate::{
     error::AniRustError,
-    proxy::Proxy,
-    utils::{anirust_error_vec_to_string, bytes_to_hex, decrypt_aes_256_cbc, get_curl},
+    transport::Transport,
+    utils::{decrypt_aes_256_cbc, get_curl},
 };
+use std::convert::Infallible;
+use std::str::FromStr;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -18,18 +21,12 @@ pub struct Track {
     pub default: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct IntroOutro {
     pub start: u32,
     pub end: u32,
 }
 
-impl Default for IntroOutro {
-    fn default() -> Self {
-        IntroOutro { start: 0, end: 0 }
-    }
-}
-
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MegaCloudUnencryptedSrc {
     pub file: String,
@@ -68,16 +65,6 @@ const MEGACLOUD: MegaCloud = MegaCloud {
     sources: "https://megacloud.tv/embed-2/ajax/e-1/getSources?id=",
 };
 
-struct StreamSb {
-    pub host1: &'static str,
-    pub host2: &'static str,
-}
-
-const STREAMSB: StreamSb = StreamSb {
-    host1: "https://watchsb.com/sources50",
-    host2: "https://streamsss.net/sources16",
-};
-
 #[derive(Debug, PartialEq, Eq)]
 pub enum AnimeServer {
     Vidstreaming,
@@ -87,18 +74,23 @@ pub enum AnimeServer {
     Vidcloud,
 }
 
-impl AnimeServer {
-    pub fn from_str(s: &str) -> Self {
-        match s {
+// Unknown names fall back to Vidstreaming
+impl FromStr for AnimeServer {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
             "vidsrc" => AnimeServer::Vidstreaming,
             "megacloud" => AnimeServer::Megacloud,
             "streamsb" => AnimeServer::Streamsb,
             "streamtape" => AnimeServer::Streamtape,
             "vidcloud" => AnimeServer::Vidcloud,
             _ => AnimeServer::Vidstreaming,
-        }
+        })
     }
+}
 
+impl AnimeServer {
     pub fn as_str(&self) -> &str {
         match self {
             AnimeServer::Vidstreaming => "vidsrc",
@@ -117,16 +109,21 @@ pub enum EpisodeType {
     Raw,
 }
 
-impl EpisodeType {
-    pub fn from_str(s: &str) -> Self {
-        match s {
+// Unknown names fall back to Sub
+impl FromStr for EpisodeType {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
             "sub" => EpisodeType::Sub,
             "dub" => EpisodeType::Dub,
             "raw" => EpisodeType::Raw,
             _ => EpisodeType::Sub,
-        }
+        })
     }
+}
 
+impl EpisodeType {
     pub fn as_str(&self) -> &str {
         match self {
             EpisodeType::Sub => "sub",
@@ -147,11 +144,11 @@ pub struct MegaCloudServer;
 impl MegaCloudServer {
     pub async fn extract(
         video_url: &str,
-        proxies: &[Proxy],
+        transport: &Transport,
     ) -> Result<ServerExtractedInfo, AniRustError> {
         let video_id = extract_video_id(video_url);
         let url = format!("{}{}", MEGACLOUD.sources, video_id);
-        let json_data = fetch_initial_data(&url, proxies).await?;
+        let json_data = fetch_initial_data(&url, transport).await?;
 
         let is_encrypted = json_data["encrypted"].as_bool().unwrap_or(false);
         let intro: IntroOutro = parse_json_field(&json_data, "intro").unwrap_or_default();
@@ -160,7 +157,7 @@ impl MegaCloudServer {
 
         let sources = if is_encrypted {
             let encrypted_string = extract_encrypted_string(&json_data);
-            let decrypted_sources = decrypt_sources(&encrypted_string, proxies).await?;
+            let decrypted_sources = decrypt_sources(&encrypted_string, transport).await?;
             parse_sources(&decrypted_sources)?
         } else {
             parse_json_field(&json_data, "sources")?
@@ -180,25 +177,9 @@ pub struct StreamTapeServer;
 impl StreamTapeServer {
     pub async fn extract(
         video_url: &str,
-        proxies: &[Proxy],
+        transport: &Transport,
     ) -> Result<ServerExtractedInfo, AniRustError> {
-        let mut error_vec = vec![];
-        let mut curl = String::new();
-
-        match get_curl(video_url, proxies).await {
-            Ok(curl_string) => {
-                curl = curl_string;
-            }
-            Err(e) => {
-                error_vec.push(Some(e));
-            }
-        }
-
-        if curl.is_empty() {
-            let error_string = anirust_error_vec_to_string(error_vec);
-            return Err(AniRustError::UnknownError(error_string));
-        }
-
+        let curl = get_curl(transport, video_url).await?;
         let document = Html::parse_document(&curl);
 
         let re = Regex::new(r"robotlink'\).innerHTML = (.*)'").unwrap();
@@ -221,7 +202,10 @@ impl StreamTapeServer {
             }
         }
 
-        Err(AniRustError::FailedToFetchAfterRetries)
+        Err(AniRustError::ExtractionFailed {
+            server: AnimeServer::Streamtape.as_str().to_string(),
+            reason: "video link not found".to_string(),
+        })
     }
 }
 
@@ -232,7 +216,7 @@ impl StreamTapeServer {
 //     pub async fn extract(
 //         video_url: &str,
 //         is_alt: Option<bool>,
-//         proxies: &[Proxy],
+//         proxies: &ProxyPool,
 //     ) -> Result<(), AniRustError> {
 //         let encoded_id = get_encoded_video_id(video_url);
 //         let hexed_id = bytes_to_hex(&encoded_id);
@@ -272,13 +256,11 @@ fn matching_key(value: &str, script: &str) -> Result<String, AniRustError> {
     if let Some(captures) = regex.captures(script) {
         let match_str = captures
             .get(1)
-            .ok_or_else(|| AniRustError::UnknownError("Failed to capture key".to_string()))?
+            .ok_or_else(megacloud_key_not_found)?
             .as_str();
         Ok(match_str.trim_start_matches("0x").to_string())
     } else {
-        Err(AniRustError::UnknownError(
-            "Failed to match the key".to_string(),
-        ))
+        Err(megacloud_key_not_found())
     }
 }
 
@@ -290,11 +272,14 @@ fn get_secret(encrypted_string: &str, values: &Vec<(u32, u32)>) -> (String, Stri
     for &(start_offset, length) in values {
         let start = start_offset as usize + current_index;
         let end = start + length as usize;
-        for i in start..end {
-            if let Some(ch) = encrypted_string.chars().nth(i) {
-                secret.push(ch);
-                encrypted_source_array[i] = '\0';
-            }
+        for (ch, slot) in encrypted_string
+            .chars()
+            .zip(encrypted_source_array.iter_mut())
+            .take(end)
+            .skip(start)
+        {
+            secret.push(ch);
+            *slot = '\0';
         }
         current_index += length as usize;
     }
@@ -316,10 +301,10 @@ fn decrypt(
         (
             key_or_secret.as_bytes().to_vec(),
             iv,
-            base64::decode(encrypted).unwrap_or_default(),
+            STANDARD.decode(encrypted).unwrap_or_default(),
         )
     } else {
-        let cypher = base64::decode(encrypted).unwrap_or_default();
+        let cypher = STANDARD.decode(encrypted).unwrap_or_default();
         let salt = &cypher[8..16];
         let password = [key_or_secret.as_bytes(), salt].concat();
 
@@ -346,15 +331,18 @@ fn decrypt(
 fn extract_video_id(video_url: &str) -> String {
     video_url
         .split('/')
-        .last()
+        .next_back()
         .and_then(|s| s.split('?').next())
         .unwrap_or_default()
         .to_string()
 }
 
-async fn fetch_initial_data(url: &str, proxies: &[Proxy]) -> Result<Value, AniRustError> {
-    let response = get_curl(url, proxies).await?;
-    serde_json::from_str(&response).map_err(|e| AniRustError::UnknownError(e.to_string()))
+async fn fetch_initial_data(url: &str, transport: &Transport) -> Result<Value, AniRustError> {
+    let response = get_curl(transport, url).await?;
+    serde_json::from_str(&response).map_err(|e| AniRustError::InvalidResponse {
+        url: url.to_string(),
+        reason: e.to_string(),
+    })
 }
 
 fn parse_json_field<T: serde::de::DeserializeOwned>(
@@ -362,7 +350,7 @@ fn parse_json_field<T: serde::de::DeserializeOwned>(
     field: &str,
 ) -> Result<T, AniRustError> {
     serde_json::from_value(json[field].clone())
-        .map_err(|e| AniRustError::UnknownError(format!("Failed to parse {}: {}", field, e)))
+        .map_err(|e| megacloud_extraction_failed(format!("Failed to parse {}: {}", field, e)))
 }
 
 fn extract_encrypted_string(json: &Value) -> String {
@@ -375,70 +363,40 @@ fn extract_encrypted_string(json: &Value) -> String {
 
 async fn decrypt_sources(
     encrypted_string: &str,
-    proxies: &[Proxy],
+    transport: &Transport,
 ) -> Result<String, AniRustError> {
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .map_err(|e| AniRustError::UnknownError(e.to_string()))?
         .as_millis();
 
-    let full_url = format!(
-        "https://megacloud.tv/js/player/a/prod/e1-player.min.js?v={}",
-        now
-    );
-    let script = get_curl(&full_url, proxies).await?;
+    let full_url = format!("{}{}", MEGACLOUD.script, now);
+    let script = get_curl(transport, &full_url).await?;
 
     let variables = extract_variables(&script)?;
     if variables.is_empty() {
-        return Err(AniRustError::UnknownError(
-            "Can't find variables. Perhaps the extractor is outdated.".to_string(),
-        ));
+        return Err(megacloud_key_not_found());
     }
 
     let (secret, encrypted_source) = get_secret(encrypted_string, &variables);
-    let decrypted = decrypt(&encrypted_source, &secret, None)?;
-    Ok(decrypted)
+    decrypt(&encrypted_source, &secret, None)
+        .map_err(|e| megacloud_extraction_failed(format!("Failed to decrypt sources: {}", e)))
 }
 
 fn parse_sources(decrypted: &str) -> Result<Vec<Source>, AniRustError> {
     serde_json::from_str(decrypted)
-        .map_err(|e| AniRustError::UnknownError(format!("Failed to parse sources: {}", e)))
-}
-
-fn get_payload(hex: &str) -> String {
-    // `5363587530696d33443675687c7c{hex}7c7c433569475830474c497a65767c7c73747265616d7362`;
-    let payload = format!("566d337678566f743674494a7c7c{}7c7c346b6767586d6934774855537c7c73747265616d7362/6565417268755339773461447c7c346133383438333436313335376136323337373433383634376337633465366534393338373136643732373736343735373237613763376334363733353737303533366236333463353333363534366137633763373337343732363536313664373336327c7c6b586c3163614468645a47617c7c73747265616d7362", hex);
-
-    payload
+        .map_err(|e| megacloud_extraction_failed(format!("Failed to parse sources: {}", e)))
 }
 
-fn get_encoded_video_id(video_url: &str) -> Vec<u8> {
-    let mut id = video_url
-        .split("/e/")
-        .last()
-        .unwrap_or_default()
-        .to_string();
-
-    if id.contains("html") {
-        id = id.split(".html").next().unwrap_or_default().to_string();
+fn megacloud_key_not_found() -> AniRustError {
+    AniRustError::DecryptionKeyNotFound {
+        server: AnimeServer::Megacloud.as_str().to_string(),
     }
-
-    id.as_bytes().to_vec()
 }
 
-async fn process_streamsb_url(
-    is_alt: Option<bool>,
-    hexed_id: &str,
-    proxies: &[Proxy],
-) -> Result<String, AniRustError> {
-    let host = if matches!(is_alt, Some(true)) {
-        &STREAMSB.host2
-    } else {
-        &STREAMSB.host1
-    };
-
-    let url = format!("{}/{}", host, hexed_id);
-    let res = get_curl(&url, proxies).await?;
-
-    Ok(res)
+fn megacloud_extraction_failed(reason: String) -> AniRustError {
+    AniRustError::ExtractionFailed {
+        server: AnimeServer::Megacloud.as_str().to_string(),
+        reason,
+    }
 }
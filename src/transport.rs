@@ -0,0 +1,218 @@
+// transport.rs
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use reqwest::{header::HeaderMap, StatusCode};
+
+use crate::{
+    config::ClientConfig,
+    error::AniRustError,
+    proxy::{Proxy, ProxyPool},
+    rate_limit::RateLimiter,
+    retry::RetryPolicy,
+    utils::decode_body,
+};
+
+/// A single outgoing GET request handed to a [`Fetcher`].
+#[derive(Debug, Clone)]
+pub struct FetchRequest {
+    pub url: String,
+    pub headers: HeaderMap,
+    /// Proxy picked for this attempt, if any. Fetchers that do not support
+    /// proxies are free to ignore it.
+    pub proxy: Option<Proxy>,
+    pub timeout: Duration,
+}
+
+/// Raw response returned by a [`Fetcher`]. The body is already decompressed.
+#[derive(Debug, Clone)]
+pub struct FetchResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+/// HTTP transport used by `HiAnimeRust` and the `servers` extractors.
+///
+/// Implement this to plug in a custom client, a mock or a recording layer.
+/// Retries and proxy rotation are handled by the caller, so an implementation
+/// only has to perform a single request.
+#[async_trait]
+pub trait Fetcher: fmt::Debug + Send + Sync {
+    async fn fetch(&self, request: FetchRequest) -> Result<FetchResponse, AniRustError>;
+}
+
+// Per-proxy clients kept alive by default
+const DEFAULT_PROXY_CLIENT_CAPACITY: usize = 64;
+
+/// Default [`Fetcher`] backed by `reqwest`.
+///
+/// Holds one long-lived client for direct traffic and a bounded cache of
+/// per-proxy clients, so connections and TLS sessions are reused across
+/// requests. The least recently used proxy client is dropped when the cache
+/// is full. Clones share the same clients.
+#[derive(Debug, Clone)]
+pub struct ReqwestFetcher {
+    direct: reqwest::Client,
+    proxied: Arc<Mutex<HashMap<Proxy, ProxyClient>>>,
+    proxy_client_capacity: usize,
+}
+
+#[derive(Debug)]
+struct ProxyClient {
+    client: reqwest::Client,
+    last_used: Instant,
+}
+
+impl Default for ReqwestFetcher {
+    fn default() -> Self {
+        Self::with_proxy_client_capacity(DEFAULT_PROXY_CLIENT_CAPACITY)
+    }
+}
+
+impl ReqwestFetcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fetcher that keeps at most `capacity` per-proxy clients alive
+    pub fn with_proxy_client_capacity(capacity: usize) -> Self {
+        ReqwestFetcher {
+            direct: reqwest::Client::new(),
+            proxied: Arc::new(Mutex::new(HashMap::new())),
+            proxy_client_capacity: capacity.max(1),
+        }
+    }
+
+    /// Proxies that currently have a pooled client
+    pub fn cached_proxies(&self) -> Vec<Proxy> {
+        self.proxied.lock().unwrap().keys().cloned().collect()
+    }
+
+    // Client for `proxy`, built on first use
+    fn proxy_client(&self, proxy: &Proxy) -> Result<reqwest::Client, AniRustError> {
+        let mut clients = self.proxied.lock().unwrap();
+        let now = Instant::now();
+
+        if let Some(cached) = clients.get_mut(proxy) {
+            cached.last_used = now;
+            return Ok(cached.client.clone());
+        }
+
+        let client = reqwest::Client::builder()
+            .proxy(proxy.to_reqwest()?)
+            .build()?;
+
+        if clients.len() >= self.proxy_client_capacity {
+            let oldest = clients
+                .iter()
+                .min_by_key(|(_, cached)| cached.last_used)
+                .map(|(proxy, _)| proxy.clone());
+            if let Some(oldest) = oldest {
+                clients.remove(&oldest);
+            }
+        }
+        clients.insert(
+            proxy.clone(),
+            ProxyClient {
+                client: client.clone(),
+                last_used: now,
+            },
+        );
+
+        Ok(client)
+    }
+}
+
+#[async_trait]
+impl Fetcher for ReqwestFetcher {
+    async fn fetch(&self, request: FetchRequest) -> Result<FetchResponse, AniRustError> {
+        let client = match &request.proxy {
+            Some(proxy) => self.proxy_client(proxy)?,
+            None => self.direct.clone(),
+        };
+
+        let response = client
+            .get(&request.url)
+            .headers(request.headers)
+            .timeout(request.timeout)
+            .send()
+            .await?;
+
+        let status = response.status();
+        let headers = response.headers().to_owned();
+        let res_bytes = response.bytes().await?;
+        let body = decode_body(&headers, &res_bytes);
+
+        Ok(FetchResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+/// Everything a request needs: the fetcher, the proxy pool, the rate limiter and
+/// the client settings.
+///
+/// Cheap to clone; clones share the same pool, limiter and fetcher.
+#[derive(Debug, Clone)]
+pub struct Transport {
+    fetcher: Arc<dyn Fetcher>,
+    proxies: Arc<ProxyPool>,
+    limiter: Arc<RateLimiter>,
+    config: Arc<ClientConfig>,
+}
+
+impl Transport {
+    pub fn new(
+        fetcher: Arc<dyn Fetcher>,
+        proxies: Arc<ProxyPool>,
+        limiter: Arc<RateLimiter>,
+        config: Arc<ClientConfig>,
+    ) -> Self {
+        Transport {
+            fetcher,
+            proxies,
+            limiter,
+            config,
+        }
+    }
+
+    pub fn fetcher(&self) -> &Arc<dyn Fetcher> {
+        &self.fetcher
+    }
+
+    pub fn proxies(&self) -> &Arc<ProxyPool> {
+        &self.proxies
+    }
+
+    pub fn limiter(&self) -> &Arc<RateLimiter> {
+        &self.limiter
+    }
+
+    pub fn config(&self) -> &ClientConfig {
+        &self.config
+    }
+
+    /// Same transport, but retrying with `policy`
+    pub fn with_retry_policy(&self, policy: RetryPolicy) -> Self {
+        let mut config = ClientConfig::clone(&self.config);
+        config.retry_policy = policy;
+        self.with_config(config)
+    }
+
+    /// Same transport, with `config` in place of the current settings
+    pub fn with_config(&self, config: ClientConfig) -> Self {
+        Transport {
+            fetcher: self.fetcher.clone(),
+            proxies: self.proxies.clone(),
+            limiter: self.limiter.clone(),
+            config: Arc::new(config),
+        }
+    }
+}
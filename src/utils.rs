@@ -1,211 +1,264 @@
 use crate::{
-    env::EnvVar,
     error::AniRustError,
-    proxy::{get_random_proxy, Proxy},
+    transport::{FetchRequest, FetchResponse, Transport},
 };
 use brotli::Decompressor;
-use core::fmt;
+use chrono::{DateTime, Utc};
 use flate2::read::{GzDecoder, ZlibDecoder};
-use http_body_util::{BodyExt, Empty};
-use hyper::body::Bytes;
-use hyper_tls::HttpsConnector;
-use hyper_util::rt::TokioExecutor;
 use openssl::symm::{Cipher, Crypter, Mode};
-use reqwest::header;
+use reqwest::{
+    header::{self, HeaderMap, HeaderValue},
+    StatusCode,
+};
 use serde_json::Value;
 use std::io::Read;
 use std::time::Duration;
+use tokio::time::Instant;
+
+// Longest `Retry-After` delay that is honoured; longer ones are capped
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+// Markers of Cloudflare interstitials served instead of the real page. Regular
+// pages can load `/cdn-cgi/challenge-platform/` scripts, so that path is not one
+const ANTI_BOT_MARKERS: [&str; 2] = ["<title>Just a moment...</title>", "_cf_chl_opt"];
 
 /// Fetches data from the specified URL.
 ///
-/// Returns the HTML content of the page as a string.
-/// TODO: find a way to do it using hyper , to reduce no of dependencies
-pub async fn get_curl(url: &str, proxies: &[Proxy]) -> Result<String, AniRustError> {
-    let max_attempts = parse_usize(&EnvVar::MAX_RETRIES_ATTEMPTS.get_config()).unwrap_or(50);
-    let timeout_duration = Duration::from_secs(5);
+/// Returns the HTML content of the page as a string. 429 and 5xx responses are
+/// retried, honouring `Retry-After`; a 404 fails with [`AniRustError::NotFound`]
+/// and anti-bot pages with [`AniRustError::AntiBotChallenge`].
+pub async fn get_curl(transport: &Transport, url: &str) -> Result<String, AniRustError> {
+    fetch_with_retries(transport, url, transport.config().headers.clone()).await
+}
 
-    let mut attempt = 0;
-    while attempt < max_attempts {
-        let client = if proxies.is_empty() {
-            reqwest::Client::builder()
-                .timeout(timeout_duration)
-                .build()?
-        } else {
-            let proxy = get_random_proxy(proxies).unwrap();
-            reqwest::Client::builder()
-                .proxy(reqwest::Proxy::http(&proxy.address)?)
-                .timeout(timeout_duration)
-                .build()?
-        };
+// Sends the request through a proxy from the pool on every attempt until one
+// succeeds, backing off between attempts as the retry policy says
+async fn fetch_with_retries(
+    transport: &Transport,
+    url: &str,
+    headers: HeaderMap,
+) -> Result<String, AniRustError> {
+    let policy = &transport.config().retry_policy;
+    let proxies = transport.proxies();
+    let started = Instant::now();
 
-        let response = match client
-            .get(url)
-            .header(header::USER_AGENT, &EnvVar::USER_AGENT_HEADER.get_config())
-            .header(
-                header::ACCEPT_ENCODING,
-                &EnvVar::ACCEPT_ENCODING_HEADER.get_config(),
-            )
-            .header(header::ACCEPT, &EnvVar::ACCEPT_HEADER.get_config())
-            .send()
-            .await
-        {
-            Ok(resp) => resp,
-            Err(_) if attempt < max_attempts - 1 => {
-                attempt += 1;
-                continue;
+    let mut attempts = 0;
+    let mut last_error = None;
+    while attempts < policy.max_attempts {
+        // Time spent waiting on the rate limiter counts against the deadline too
+        let permit = match policy.deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_sub(started.elapsed());
+                match tokio::time::timeout(remaining, transport.limiter().acquire(url)).await {
+                    Ok(permit) => permit,
+                    Err(_) => break,
+                }
             }
-            Err(e) => return Err(AniRustError::ReqwestError(e)),
+            None => transport.limiter().acquire(url).await,
+        };
+
+        // Never let an attempt run past the deadline
+        let timeout = match policy.deadline {
+            Some(deadline) => match deadline.checked_sub(started.elapsed()) {
+                Some(remaining) if !remaining.is_zero() => remaining.min(policy.attempt_timeout),
+                _ => break,
+            },
+            None => policy.attempt_timeout,
         };
+        attempts += 1;
 
-        let res_headers = response.headers().to_owned();
-        let content_encoding = res_headers
-            .get(header::CONTENT_ENCODING)
-            .and_then(|v| v.to_str().ok())
-            .unwrap_or("");
+        let proxy = proxies.select();
+        let request = FetchRequest {
+            url: url.to_string(),
+            headers: headers.clone(),
+            proxy: proxy.clone(),
+            timeout,
+        };
 
-        let res_bytes = response.bytes().await?;
+        let start = Instant::now();
+        let result = transport.fetcher().fetch(request).await;
+        let elapsed = start.elapsed();
+        drop(permit);
 
-        let body = match content_encoding {
-            "gzip" => {
-                let mut decoded = Vec::new();
-                let mut gz = GzDecoder::new(res_bytes.as_ref());
-                gz.read_to_end(&mut decoded).unwrap_or_default();
-                String::from_utf8(decoded).unwrap_or_default()
+        // Whether the proxy did its job, even if the site answered with an error
+        let record = |proxy_ok: bool| {
+            if let Some(proxy) = &proxy {
+                if proxy_ok {
+                    proxies.record_success(proxy, elapsed);
+                } else {
+                    proxies.record_failure(proxy);
+                }
             }
-            "deflate" => {
-                let mut zf = ZlibDecoder::new(res_bytes.as_ref());
-                let mut decoded = Vec::new();
-                zf.read_to_end(&mut decoded).unwrap_or_default();
-                String::from_utf8(decoded).unwrap_or_default()
+        };
+
+        // Delay asked for by the server, which takes precedence over the backoff
+        let mut requested_delay = None;
+
+        match result {
+            // Another attempt cannot help when a replay has no fixture for the URL
+            Err(e @ AniRustError::NoRecordedResponse(_)) => return Err(e),
+            // Whatever else went wrong happened while the proxy was in use
+            Err(e) => {
+                record(false);
+                last_error = Some(e);
             }
-            "br" => {
-                let mut decompressor = Decompressor::new(res_bytes.as_ref(), 4096);
-                let mut decoded = Vec::new();
-                decompressor.read_to_end(&mut decoded).unwrap_or_default();
-                String::from_utf8(decoded).unwrap_or_default()
+            Ok(response) if is_anti_bot_challenge(&response) => {
+                // The challenge is tied to the IP, so only another proxy can get past it
+                record(false);
+                let error = AniRustError::AntiBotChallenge {
+                    url: url.to_string(),
+                };
+                if proxy.is_none() {
+                    return Err(error);
+                }
+                last_error = Some(error);
             }
-            _ => String::from_utf8(res_bytes.to_vec()).unwrap_or_default(),
-        };
+            Ok(response) => {
+                let status = response.status;
+                if status.is_success() {
+                    record(true);
+                    return Ok(response.body);
+                } else if status == StatusCode::NOT_FOUND {
+                    record(true);
+                    return Err(AniRustError::NotFound {
+                        url: url.to_string(),
+                    });
+                } else if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+                    // Rate limits usually apply per IP, server errors do not
+                    record(status != StatusCode::TOO_MANY_REQUESTS);
+                    requested_delay = retry_after(&response.headers);
+                    last_error = Some(AniRustError::HttpStatus {
+                        url: url.to_string(),
+                        status,
+                    });
+                } else {
+                    record(true);
+                    return Err(AniRustError::HttpStatus {
+                        url: url.to_string(),
+                        status,
+                    });
+                }
+            }
+        }
 
-        return Ok(body);
+        if attempts < policy.max_attempts {
+            let delay = match requested_delay {
+                Some(delay) => delay.min(MAX_RETRY_AFTER),
+                None => policy.delay(attempts),
+            };
+            if policy
+                .deadline
+                .is_some_and(|deadline| started.elapsed() + delay >= deadline)
+            {
+                break;
+            }
+            tokio::time::sleep(delay).await;
+        }
     }
 
-    Err(AniRustError::FailedToFetchAfterRetries)
-}
-
-pub fn parse_usize(s: &str) -> Result<usize, AniRustError> {
-    s.to_string()
-        .parse::<usize>()
-        .map_err(AniRustError::ParseIntError)
+    match last_error {
+        Some(error @ AniRustError::AntiBotChallenge { .. }) => Err(error),
+        last_error => Err(AniRustError::FailedToFetchAfterRetries {
+            url: url.to_string(),
+            attempts,
+            last_error: last_error.map(Box::new),
+        }),
+    }
 }
 
-pub fn stringify<T: fmt::Display>(input: T) -> String {
-    format!("{}", input)
-}
+// Recognises Cloudflare style challenge pages by header or by their markup
+fn is_anti_bot_challenge(response: &FetchResponse) -> bool {
+    let mitigated = response
+        .headers
+        .get("cf-mitigated")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.eq_ignore_ascii_case("challenge"));
 
-pub fn anirust_error_vec_to_string(error_vec: Vec<Option<AniRustError>>) -> String {
-    error_vec
-        .iter()
-        .filter_map(|opt_error| opt_error.as_ref().map(|e| e.to_string()))
-        .collect::<Vec<String>>()
-        .join(", ")
+    mitigated
+        || ANTI_BOT_MARKERS
+            .iter()
+            .any(|marker| response.body.contains(marker))
 }
 
-// TODO: find a way to impl proxies
-pub async fn get_ajax_curl(url: &str, field: &str) -> Result<String, AniRustError> {
-    // Create an HTTPS connector
-    let https = HttpsConnector::new();
-    // Create an HTTP client
-    let client = hyper_util::client::legacy::Client::builder(TokioExecutor::new())
-        .build::<_, Empty<Bytes>>(https);
-
-    // Define the URL
-    let url = url.parse().unwrap_or_default();
+// Parses `Retry-After`, given either in seconds or as an HTTP date
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(header::RETRY_AFTER)?.to_str().ok()?.trim();
 
-    // Make the GET request
-    let res = client
-        .get(url)
-        .await
-        .unwrap()
-        .map_err(|_| AniRustError::UnknownError("hyper client failed".to_string()));
-
-    // Collect the response body
-    let body = res.collect().await?;
-    let body_bytes = body.to_bytes();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
 
-    // Convert body bytes to a UTF-8 string
-    let body_string = String::from_utf8(body_bytes.to_vec()).unwrap_or_default();
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&Utc) - Utc::now()).to_std().ok()
+}
 
-    // Parse the string as JSON
-    let json_value = serde_json::from_str::<Value>(&body_string).unwrap_or_default();
+/// Decompresses a response body according to its `Content-Encoding` header.
+pub fn decode_body(headers: &HeaderMap, res_bytes: &[u8]) -> String {
+    let content_encoding = headers
+        .get(header::CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
 
-    match json_value.get(field) {
-        Some(data) => {
-            Ok(serde_json::from_str::<String>(data.to_string().as_str()).unwrap_or_default())
+    match content_encoding {
+        "gzip" => {
+            let mut decoded = Vec::new();
+            let mut gz = GzDecoder::new(res_bytes);
+            gz.read_to_end(&mut decoded).unwrap_or_default();
+            String::from_utf8(decoded).unwrap_or_default()
         }
-        None => Ok(String::new()),
+        "deflate" => {
+            let mut zf = ZlibDecoder::new(res_bytes);
+            let mut decoded = Vec::new();
+            zf.read_to_end(&mut decoded).unwrap_or_default();
+            String::from_utf8(decoded).unwrap_or_default()
+        }
+        "br" => {
+            let mut decompressor = Decompressor::new(res_bytes, 4096);
+            let mut decoded = Vec::new();
+            decompressor.read_to_end(&mut decoded).unwrap_or_default();
+            String::from_utf8(decoded).unwrap_or_default()
+        }
+        _ => String::from_utf8(res_bytes.to_vec()).unwrap_or_default(),
     }
 }
 
-pub fn substring_after(str: &str, to_find: &str) -> String {
-    let index = str.find(to_find);
-    match index {
-        Some(i) => str[i + to_find.len()..].to_string(),
-        None => String::new(),
-    }
-}
+/// Fetches an AJAX endpoint that answers with HTML rather than JSON.
+///
+/// Uses the same proxy rotation, retries and headers as [`get_curl`], plus
+/// the `X-Requested-With` header the site expects on AJAX requests.
+pub async fn get_ajax_html(transport: &Transport, url: &str) -> Result<String, AniRustError> {
+    let mut headers = transport.config().headers.clone();
+    headers.insert(
+        header::HeaderName::from_static("x-requested-with"),
+        HeaderValue::from_static("XMLHttpRequest"),
+    );
 
-pub fn substring_before(str: &str, to_find: &str) -> String {
-    let index = str.find(to_find);
-    match index {
-        Some(i) => str[..i].to_string(),
-        None => String::new(),
-    }
+    fetch_with_retries(transport, url, headers).await
 }
 
-// Encrypts a plaintext message using AES-256 in CBC (Cipher Block Chaining) mode.
-// //
-// // # Arguments
-// //
-// // * `iv` - An initialization vector (IV) used to provide the starting state for encryption.
-// Must be 16 bytes long.
-// // * `key` - A secret key used to perform encryption. Must be 32 bytes long for AES-256.
-// // * `text` - The plaintext message to be encrypted, represented as a byte slice.
-// //
-// // # Returns
-// //
-// // Returns a `Vec<u8>` containing the encrypted ciphertext.
-// //
-// // # Example
-// //
-// // ```rust
-// // use aes::encrypt_aes_256_cbc;
-// // let iv = b"1234567890123456";
-// // let key = b"01234567890123456789012345678901";
-// // let text = b"Hello, world!";
-// // let ciphertext = encrypt_aes_256_cbc(iv, key, text);
-// // assert_eq!(ciphertext, [197, 221, 61, 37, 184, 139, 38, 189, 182, 53, 144, 45, 170, 182, 220,
-// 210, 64, 155, 36, 239, 138, 38, 33, 48, 25, 101, 160, 99, 8, 24, 111, 137]);
-// // ```
-// //
-// // # Errors
-// //
-// // This function will panic if the initialization vector or key lengths are incorrect.
-pub fn encrypt_aes_256_cbc(iv: &[u8], key: &[u8], text: &[u8]) -> Vec<u8> {
-    let cipher = Cipher::aes_256_cbc();
-    let mut encrypter = Crypter::new(cipher, Mode::Encrypt, key, Some(iv)).unwrap();
+/// Fetches a JSON AJAX endpoint and returns the string stored under `field`.
+///
+/// Sent like [`get_ajax_html`].
+pub async fn get_ajax_curl(
+    transport: &Transport,
+    url: &str,
+    field: &str,
+) -> Result<String, AniRustError> {
+    let body = get_ajax_html(transport, url).await?;
 
-    let block_size = cipher.block_size();
-    let mut encrypted_data = vec![0; text.len() + block_size];
-    let count = encrypter
-        .update(text, &mut encrypted_data)
-        .unwrap_or_default();
-    let rest = encrypter
-        .finalize(&mut encrypted_data[count..])
-        .unwrap_or_default();
-    encrypted_data.truncate(count + rest);
+    let json_value =
+        serde_json::from_str::<Value>(&body).map_err(|e| AniRustError::InvalidResponse {
+            url: url.to_string(),
+            reason: format!("Failed to parse AJAX response: {}", e),
+        })?;
 
-    encrypted_data
+    match json_value.get(field).and_then(Value::as_str) {
+        Some(data) => Ok(data.to_string()),
+        None => Err(AniRustError::InvalidResponse {
+            url: url.to_string(),
+            reason: format!("AJAX response has no `{}` field", field),
+        }),
+    }
 }
 
 // Decrypts an AES-256 encrypted message using CBC (Cipher Block Chaining) mode.
@@ -253,7 +306,3 @@ pub fn decrypt_aes_256_cbc(iv: &[u8], key: &[u8], encrypted_data: &[u8]) -> Vec<
 
     decrypted_data
 }
-
-pub fn bytes_to_hex(bytes: &[u8]) -> String {
-    bytes.iter().map(|b| format!("{:02x}", b)).collect()
-}
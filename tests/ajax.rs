@@ -0,0 +1,68 @@
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use aniscraper::{
+    error::AniRustError,
+    proxy::{ProxyKind, ProxySource},
+    retry::RetryPolicy,
+};
+use reqwest::StatusCode;
+
+use common::{response, temp_dir, ScriptedFetcher};
+
+const EMPTY_AJAX: &str = r#"{"status":true,"html":""}"#;
+
+#[tokio::test]
+async fn ajax_requests_go_through_the_proxy_pool_and_retry_loop() {
+    let dir = temp_dir("ajax-proxies");
+    std::fs::create_dir_all(&dir).unwrap();
+    let proxies = dir.join("proxies.txt");
+    std::fs::write(&proxies, "1.1.1.1:1080\n2.2.2.2:1080\n").unwrap();
+
+    let fetcher = Arc::new(
+        ScriptedFetcher::body(EMPTY_AJAX)
+            .then(Ok(response(StatusCode::BAD_GATEWAY, "")))
+            .then(Err(AniRustError::UnknownError("proxy refused".to_string()))),
+    );
+    let hianime = common::builder(fetcher.clone())
+        .retry_policy(RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        })
+        .proxy_source(ProxySource::File {
+            path: proxies,
+            kind: ProxyKind::Socks5,
+        })
+        .build()
+        .await
+        .unwrap();
+
+    hianime.scrape_search_suggestions("frieren").await.unwrap();
+    hianime
+        .scrape_characters("frieren-beyond-journeys-end-18542", 1)
+        .await
+        .unwrap();
+
+    let requests = fetcher.requests();
+    // Two failed attempts at the suggestions, then one request each
+    assert_eq!(requests.len(), 4);
+    assert_eq!(
+        requests[0].url,
+        "https://hianime.test/ajax/search/suggest?keyword=frieren"
+    );
+    assert_eq!(requests[2].url, requests[0].url);
+    for request in &requests {
+        let proxy = request
+            .proxy
+            .as_ref()
+            .expect("AJAX request sent without a proxy");
+        assert_eq!(proxy.kind, ProxyKind::Socks5);
+        assert_eq!(request.headers["x-requested-with"], "XMLHttpRequest");
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
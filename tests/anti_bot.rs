@@ -0,0 +1,71 @@
+mod common;
+
+use std::sync::Arc;
+
+use aniscraper::{error::AniRustError, hianime::HiAnimeRust, retry::RetryPolicy};
+use reqwest::{header::HeaderValue, StatusCode};
+
+use common::{html_fixture, response, ScriptedFetcher};
+
+async fn client(body: String) -> HiAnimeRust {
+    common::client(Arc::new(ScriptedFetcher::body(body))).await
+}
+
+#[tokio::test]
+async fn page_with_js_detection_script_is_not_a_challenge() {
+    // Cloudflare injects this into regular pages when JS detections are on
+    let page = html_fixture("atoz").replace(
+        "</body>",
+        r#"<script src="/cdn-cgi/challenge-platform/scripts/jsd/main.js"></script></body>"#,
+    );
+
+    let atoz = client(page).await.scrape_atoz(1).await.unwrap();
+
+    assert!(!atoz.animes.is_empty());
+}
+
+#[tokio::test]
+async fn interstitial_is_a_challenge() {
+    let page = r#"<html><head><title>Just a moment...</title></head>
+<body><script>window._cf_chl_opt = {};</script></body></html>"#;
+
+    let result = client(page.to_string()).await.scrape_atoz(1).await;
+
+    match result {
+        Err(AniRustError::AllDomainsFailed(errors)) => assert!(matches!(
+            errors[0].error,
+            AniRustError::AntiBotChallenge { .. }
+        )),
+        other => panic!("expected a challenge, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[tokio::test]
+async fn cf_mitigated_header_is_a_challenge() {
+    let mut challenge = response(StatusCode::FORBIDDEN, html_fixture("atoz"));
+    challenge
+        .headers
+        .insert("cf-mitigated", HeaderValue::from_static("challenge"));
+    let fetcher = Arc::new(ScriptedFetcher::new(challenge));
+
+    let hianime = common::builder(fetcher.clone())
+        .retry_policy(RetryPolicy {
+            max_attempts: 3,
+            ..RetryPolicy::default()
+        })
+        .build()
+        .await
+        .unwrap();
+
+    let result = hianime.scrape_atoz(1).await;
+
+    match result {
+        Err(AniRustError::AllDomainsFailed(errors)) => assert!(matches!(
+            errors[0].error,
+            AniRustError::AntiBotChallenge { .. }
+        )),
+        other => panic!("expected a challenge, got {:?}", other.map(|_| ())),
+    }
+    // Without a proxy to switch to, retrying cannot get past the challenge
+    assert_eq!(fetcher.requests().len(), 1);
+}
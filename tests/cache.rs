@@ -0,0 +1,171 @@
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use aniscraper::{
+    cache::{CacheBackend, CacheKind, CacheTtls, DiskCache, MemoryCache, ResponseCache},
+    error::AniRustError,
+    hianime::HiAnimeRust,
+    parse::ParseMode,
+};
+
+use common::{temp_dir, ScriptedFetcher};
+
+const ATOZ_HTML: &str = r#"<html><body>
+<div id="main-wrapper"><div><div class="page-az-wrap"><section><div class="tab-content"><div>
+  <div class="film_list-wrap">
+    <div class="flw-item">
+      <div class="film-poster"><img class="film-poster-img" data-src="https://img.test/one-piece.jpg"></div>
+      <div class="film-detail"><h3 class="film-name"><a class="dynamic-name" href="/one-piece-100">One Piece</a></h3></div>
+    </div>
+  </div>
+</div></div></section></div></div></div>
+</body></html>"#;
+
+const ATOZ_URL: &str = "https://hianime.test/az-list?page=1";
+
+async fn client(body: &'static str) -> HiAnimeRust {
+    common::builder(Arc::new(ScriptedFetcher::body(body)))
+        .parse_mode(ParseMode::Strict)
+        .cache(ResponseCache::new(
+            MemoryCache::new(8),
+            CacheTtls::default(),
+        ))
+        .build()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn pages_that_parse_are_cached() {
+    let hianime = client(ATOZ_HTML).await;
+
+    hianime.scrape_atoz(1).await.unwrap();
+
+    let cache = hianime.cache().unwrap();
+    assert_eq!(
+        cache.get(CacheKind::AtoZ, ATOZ_URL).await.as_deref(),
+        Some(ATOZ_HTML)
+    );
+}
+
+#[tokio::test]
+async fn pages_rejected_by_the_parse_mode_are_not_cached() {
+    let hianime = client("<html><body></body></html>").await;
+
+    let error = hianime.scrape_atoz(1).await.unwrap_err();
+
+    assert!(matches!(error, AniRustError::IncompleteParse { .. }));
+    let cache = hianime.cache().unwrap();
+    assert_eq!(cache.get(CacheKind::AtoZ, ATOZ_URL).await, None);
+}
+
+const HOUR: Duration = Duration::from_secs(3600);
+
+#[tokio::test]
+async fn memory_cache_evicts_the_least_recently_used_entry() {
+    let cache = MemoryCache::new(2);
+
+    cache.put("a", "1".to_string(), HOUR).await;
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    cache.put("b", "2".to_string(), HOUR).await;
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    // Reading `a` makes `b` the least recently used entry
+    assert_eq!(cache.get("a").await.as_deref(), Some("1"));
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    cache.put("c", "3".to_string(), HOUR).await;
+
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get("a").await.as_deref(), Some("1"));
+    assert_eq!(cache.get("b").await, None);
+    assert_eq!(cache.get("c").await.as_deref(), Some("3"));
+}
+
+#[tokio::test]
+async fn memory_cache_evicts_expired_entries_before_used_ones() {
+    let cache = MemoryCache::new(2);
+
+    cache.put("a", "1".to_string(), HOUR).await;
+    cache
+        .put("b", "2".to_string(), Duration::from_millis(20))
+        .await;
+    tokio::time::sleep(Duration::from_millis(40)).await;
+    cache.put("c", "3".to_string(), HOUR).await;
+
+    assert_eq!(cache.get("a").await.as_deref(), Some("1"));
+    assert_eq!(cache.get("c").await.as_deref(), Some("3"));
+}
+
+#[tokio::test]
+async fn memory_cache_entries_expire_after_their_ttl() {
+    let cache = MemoryCache::new(8);
+
+    cache
+        .put("a", "1".to_string(), Duration::from_millis(20))
+        .await;
+    assert_eq!(cache.get("a").await.as_deref(), Some("1"));
+
+    tokio::time::sleep(Duration::from_millis(40)).await;
+    assert_eq!(cache.get("a").await, None);
+    assert!(cache.is_empty());
+}
+
+#[tokio::test]
+async fn concurrent_disk_cache_writes_leave_a_whole_entry() {
+    let dir = temp_dir("concurrent-writes");
+    let cache = Arc::new(DiskCache::new(&dir).unwrap());
+    let values: Vec<String> = (0..16).map(|i| i.to_string().repeat(4096)).collect();
+
+    let writes: Vec<_> = values
+        .iter()
+        .map(|value| {
+            let cache = cache.clone();
+            let value = value.clone();
+            tokio::spawn(async move { cache.put("key", value, HOUR).await })
+        })
+        .collect();
+    for write in writes {
+        write.await.unwrap();
+    }
+
+    let stored = cache.get("key").await.unwrap();
+    assert!(values.contains(&stored));
+    // Every temporary file was renamed into place
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn clearing_the_disk_cache_keeps_unrelated_files() {
+    let dir = temp_dir("clear-shared");
+    let cache = DiskCache::new(&dir).unwrap();
+    cache.put("key", "value".to_string(), HOUR).await;
+    for name in [
+        "notes.txt",
+        "0123456789abcdef0123456789abcdef.json",
+        "README",
+    ] {
+        std::fs::write(dir.join(name), "keep me").unwrap();
+    }
+
+    cache.clear().await;
+
+    assert_eq!(cache.get("key").await, None);
+    let mut left: Vec<String> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    left.sort();
+    assert_eq!(
+        left,
+        [
+            "0123456789abcdef0123456789abcdef.json",
+            "README",
+            "notes.txt"
+        ]
+    );
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,104 @@
+// Helpers shared by the integration tests; each test crate only uses some of them
+#![allow(dead_code)]
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use aniscraper::{
+    config::HiAnimeRustBuilder,
+    error::AniRustError,
+    hianime::HiAnimeRust,
+    rate_limit::RateLimitConfig,
+    retry::RetryPolicy,
+    transport::{FetchRequest, FetchResponse, Fetcher},
+};
+use async_trait::async_trait;
+use reqwest::{header::HeaderMap, StatusCode, Url};
+
+pub const DOMAIN: &str = "https://hianime.test";
+
+// Offline client for `DOMAIN` without retries, rate limits or environment settings
+pub fn builder(fetcher: Arc<dyn Fetcher>) -> HiAnimeRustBuilder {
+    HiAnimeRust::builder()
+        .env_fallback(false)
+        .domain(Url::parse(DOMAIN).unwrap())
+        .retry_policy(RetryPolicy::no_retries())
+        .rate_limit(RateLimitConfig::unlimited())
+        .fetcher(fetcher)
+}
+
+pub async fn client(fetcher: Arc<dyn Fetcher>) -> HiAnimeRust {
+    builder(fetcher).build().await.unwrap()
+}
+
+pub fn html_fixture(name: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/html")
+        .join(format!("{}.html", name));
+    std::fs::read_to_string(path).unwrap()
+}
+
+// Empty directory under the system temp dir, unique to this process
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("aniscraper-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+pub fn response(status: StatusCode, body: impl Into<String>) -> FetchResponse {
+    FetchResponse {
+        status,
+        headers: HeaderMap::new(),
+        body: body.into(),
+    }
+}
+
+// Answers with the scripted replies in order, then with `fallback` for good,
+// and records every request it receives
+#[derive(Debug)]
+pub struct ScriptedFetcher {
+    script: Mutex<VecDeque<Result<FetchResponse, AniRustError>>>,
+    fallback: FetchResponse,
+    requests: Mutex<Vec<FetchRequest>>,
+}
+
+impl ScriptedFetcher {
+    pub fn new(fallback: FetchResponse) -> Self {
+        ScriptedFetcher {
+            script: Mutex::new(VecDeque::new()),
+            fallback,
+            requests: Mutex::new(vec![]),
+        }
+    }
+
+    // Answers every request with a 200 and `body`
+    pub fn body(body: impl Into<String>) -> Self {
+        Self::new(response(StatusCode::OK, body))
+    }
+
+    pub fn then(self, reply: Result<FetchResponse, AniRustError>) -> Self {
+        self.script.lock().unwrap().push_back(reply);
+        self
+    }
+
+    pub fn requests(&self) -> Vec<FetchRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn urls(&self) -> Vec<String> {
+        self.requests().into_iter().map(|r| r.url).collect()
+    }
+}
+
+#[async_trait]
+impl Fetcher for ScriptedFetcher {
+    async fn fetch(&self, request: FetchRequest) -> Result<FetchResponse, AniRustError> {
+        self.requests.lock().unwrap().push(request);
+        self.script
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_else(|| Ok(self.fallback.clone()))
+    }
+}
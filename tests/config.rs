@@ -0,0 +1,380 @@
+use std::time::Duration;
+
+use aniscraper::{
+    config::{HiAnimeRustBuilder, Webhooks},
+    env::SecretConfig,
+    error::AniRustError,
+    parse::ParseMode,
+    proxy::{ProxyKind, ProxyPoolConfig, ProxySelection, ProxySource},
+    rate_limit::{RateLimit, RateLimitConfig},
+    retry::RetryPolicy,
+};
+use reqwest::{
+    header::{self, HeaderValue},
+    Url,
+};
+
+fn url(url: &str) -> Url {
+    Url::parse(url).unwrap()
+}
+
+fn builder() -> HiAnimeRustBuilder {
+    HiAnimeRustBuilder::new().env_fallback(false)
+}
+
+fn assert_invalid(builder: HiAnimeRustBuilder, what: &str) {
+    match builder.build_config() {
+        Err(AniRustError::InvalidConfig(_)) => {}
+        Err(other) => panic!("{}: expected InvalidConfig, got {:?}", what, other),
+        Ok(_) => panic!("{}: expected InvalidConfig, got a config", what),
+    }
+}
+
+fn limit(requests_per_second: f64, burst: u32, max_in_flight: usize) -> RateLimit {
+    RateLimit {
+        requests_per_second,
+        burst,
+        max_in_flight,
+    }
+}
+
+#[test]
+fn rejects_bad_domains() {
+    assert_invalid(builder().domains(vec![]), "no domain");
+    assert_invalid(builder().domain(url("ftp://hianime.test")), "ftp domain");
+    assert_invalid(builder().domain(url("mailto:me@hianime.test")), "no host");
+}
+
+#[test]
+fn rejects_bad_rate_limits() {
+    for (rate_limit, what) in [
+        (limit(0.0, 10, 16), "zero rate"),
+        (limit(-1.0, 10, 16), "negative rate"),
+        (limit(f64::NAN, 10, 16), "NaN rate"),
+        (limit(f64::INFINITY, 10, 16), "infinite rate"),
+        (limit(10.0, 0, 16), "zero burst"),
+        (limit(10.0, 10, 0), "zero in flight"),
+    ] {
+        assert_invalid(
+            builder().rate_limit(RateLimitConfig::new(rate_limit.clone())),
+            what,
+        );
+        assert_invalid(builder().host_rate_limit("megacloud.tv", rate_limit), what);
+    }
+}
+
+#[test]
+fn rejects_bad_retry_policies() {
+    for (policy, what) in [
+        (
+            RetryPolicy {
+                max_attempts: 0,
+                ..RetryPolicy::default()
+            },
+            "zero attempts",
+        ),
+        (
+            RetryPolicy {
+                attempt_timeout: Duration::ZERO,
+                ..RetryPolicy::default()
+            },
+            "zero timeout",
+        ),
+        (
+            RetryPolicy {
+                base_delay: Duration::from_secs(20),
+                max_delay: Duration::from_secs(10),
+                ..RetryPolicy::default()
+            },
+            "base delay above max delay",
+        ),
+        (
+            RetryPolicy {
+                jitter: 1.5,
+                ..RetryPolicy::default()
+            },
+            "jitter above 1",
+        ),
+        (
+            RetryPolicy {
+                jitter: -0.1,
+                ..RetryPolicy::default()
+            },
+            "negative jitter",
+        ),
+        (
+            RetryPolicy {
+                deadline: Some(Duration::ZERO),
+                ..RetryPolicy::default()
+            },
+            "zero deadline",
+        ),
+    ] {
+        assert_invalid(builder().retry_policy(policy), what);
+    }
+
+    assert_invalid(
+        builder().max_retries_attempts(0),
+        "zero max_retries_attempts",
+    );
+    assert_invalid(builder().timeout(Duration::ZERO), "zero timeout shorthand");
+}
+
+#[test]
+fn rejects_bad_urls_and_intervals() {
+    assert_invalid(
+        builder().webhooks(Webhooks {
+            utils_error: Some(url("ftp://hooks.test/utils")),
+            ..Webhooks::default()
+        }),
+        "ftp webhook",
+    );
+    assert_invalid(
+        builder().proxy_probe_url(url("file:///etc/hosts")),
+        "file probe url",
+    );
+    assert_invalid(
+        builder().proxy_refresh_interval(Duration::ZERO),
+        "zero refresh interval",
+    );
+}
+
+#[test]
+fn fully_specified_builder_round_trips() {
+    let policy = RetryPolicy {
+        max_attempts: 4,
+        base_delay: Duration::from_millis(100),
+        max_delay: Duration::from_secs(2),
+        jitter: 0.25,
+        deadline: Some(Duration::from_secs(30)),
+        attempt_timeout: Duration::from_secs(3),
+    };
+    let webhooks = Webhooks {
+        reqwest_error: Some(url("https://hooks.test/reqwest")),
+        no_proxies_available: Some(url("https://hooks.test/proxies")),
+        failed_to_fetch_after_retries: Some(url("https://hooks.test/retries")),
+        utils_error: Some(url("https://hooks.test/utils")),
+        unknown_error: Some(url("https://hooks.test/unknown")),
+    };
+    let source = ProxySource::from_location("proxies.txt", ProxyKind::Socks5);
+
+    let config = builder()
+        .retry_policy(policy.clone())
+        .domains(vec![url("https://one.test"), url("https://two.test")])
+        .header(header::USER_AGENT, HeaderValue::from_static("tests"))
+        .webhooks(webhooks.clone())
+        .proxy_source(source.clone())
+        .proxy_pool(ProxyPoolConfig {
+            selection: ProxySelection::LeastFailures,
+            max_consecutive_failures: 5,
+            quarantine_duration: Duration::from_secs(30),
+            max_quarantines: 7,
+        })
+        .proxy_refresh_interval(Duration::from_secs(600))
+        .proxy_probe_url(url("https://probe.test"))
+        .rate_limit(RateLimitConfig::new(limit(5.0, 2, 4)))
+        .host_rate_limit("MegaCloud.tv", limit(1.0, 1, 1))
+        .parse_mode(ParseMode::Strict)
+        .build_config()
+        .unwrap();
+
+    assert_eq!(config.retry_policy, policy);
+    assert_eq!(
+        config.domains,
+        [url("https://one.test"), url("https://two.test")]
+    );
+    assert_eq!(config.headers[header::USER_AGENT], "tests");
+    assert_eq!(config.webhooks.reqwest_error, webhooks.reqwest_error);
+    assert_eq!(
+        config.webhooks.no_proxies_available,
+        webhooks.no_proxies_available
+    );
+    assert_eq!(
+        config.webhooks.failed_to_fetch_after_retries,
+        webhooks.failed_to_fetch_after_retries
+    );
+    assert_eq!(config.webhooks.utils_error, webhooks.utils_error);
+    assert_eq!(config.webhooks.unknown_error, webhooks.unknown_error);
+    assert_eq!(config.proxy_sources, [source]);
+    assert_eq!(config.proxy_pool.selection, ProxySelection::LeastFailures);
+    assert_eq!(config.proxy_pool.max_consecutive_failures, 5);
+    assert_eq!(
+        config.proxy_pool.quarantine_duration,
+        Duration::from_secs(30)
+    );
+    assert_eq!(config.proxy_pool.max_quarantines, 7);
+    assert_eq!(
+        config.proxy_refresh_interval,
+        Some(Duration::from_secs(600))
+    );
+    assert_eq!(config.proxy_probe_url, Some(url("https://probe.test")));
+    assert_eq!(config.rate_limit.default, Some(limit(5.0, 2, 4)));
+    assert_eq!(
+        config.rate_limit.hosts.get("megacloud.tv"),
+        Some(&limit(1.0, 1, 1))
+    );
+    assert_eq!(config.parse_mode, ParseMode::Strict);
+}
+
+#[test]
+fn shorthands_override_the_retry_policy() {
+    let config = builder()
+        .retry_policy(RetryPolicy::no_retries())
+        .max_retries_attempts(7)
+        .timeout(Duration::from_secs(9))
+        .build_config()
+        .unwrap();
+
+    assert_eq!(config.retry_policy.max_attempts, 7);
+    assert_eq!(config.retry_policy.attempt_timeout, Duration::from_secs(9));
+}
+
+// The only test touching the process environment, so it cannot race with another
+#[test]
+fn environment_is_only_read_with_the_fallback_on() {
+    std::env::set_var(
+        "HIANIME_DOMAINS",
+        "https://env-one.test, https://env-two.test",
+    );
+    std::env::set_var("MAX_RETRIES_ATTEMPTS", "12");
+    std::env::set_var("USER_AGENT_HEADER", "env agent");
+    std::env::set_var("REQWEST_ERROR_WEBHOOK", "https://hooks.test/env");
+
+    let config = HiAnimeRustBuilder::new().build_config().unwrap();
+    assert_eq!(
+        config.domains,
+        [url("https://env-one.test"), url("https://env-two.test")]
+    );
+    assert_eq!(config.retry_policy.max_attempts, 12);
+    assert_eq!(config.headers[header::USER_AGENT], "env agent");
+    assert_eq!(
+        config.webhooks.reqwest_error,
+        Some(url("https://hooks.test/env"))
+    );
+
+    // Explicit settings win over the environment
+    let config = HiAnimeRustBuilder::new()
+        .domain(url("https://explicit.test"))
+        .max_retries_attempts(2)
+        .header(header::USER_AGENT, HeaderValue::from_static("explicit"))
+        .build_config()
+        .unwrap();
+    assert_eq!(config.domains, [url("https://explicit.test")]);
+    assert_eq!(config.retry_policy.max_attempts, 2);
+    assert_eq!(config.headers[header::USER_AGENT], "explicit");
+
+    let config = builder().build_config().unwrap();
+    assert_eq!(config.domains, [url("https://aniwatchtv.to")]);
+    assert_eq!(
+        config.retry_policy.max_attempts,
+        RetryPolicy::default().max_attempts
+    );
+    assert!(config.headers.get(header::USER_AGENT).is_none());
+    assert!(config.webhooks.reqwest_error.is_none());
+
+    std::env::set_var("MAX_RETRIES_ATTEMPTS", "many");
+    assert_invalid(HiAnimeRustBuilder::new(), "non numeric env retries");
+    assert!(builder().build_config().is_ok());
+    std::env::set_var("MAX_RETRIES_ATTEMPTS", "12");
+
+    std::env::set_var("HIANIME_DOMAINS", "not a url");
+    assert_invalid(HiAnimeRustBuilder::new(), "env domain that is not a url");
+
+    for var in [
+        "HIANIME_DOMAINS",
+        "MAX_RETRIES_ATTEMPTS",
+        "USER_AGENT_HEADER",
+        "REQWEST_ERROR_WEBHOOK",
+    ] {
+        std::env::remove_var(var);
+    }
+}
+
+fn secret(max_retries_attempts: &str, hianime_domains: &str, webhook: &str) -> SecretConfig {
+    SecretConfig::new(
+        max_retries_attempts.to_string(),
+        webhook.to_string(),
+        String::new(),
+        String::new(),
+        String::new(),
+        String::new(),
+        "http.txt".to_string(),
+        String::new(),
+        "https://lists.test/socks5.txt".to_string(),
+        hianime_domains.to_string(),
+        "secret agent".to_string(),
+        String::new(),
+        "text/html".to_string(),
+    )
+}
+
+#[test]
+fn secret_config_maps_onto_the_builder() {
+    let config = HiAnimeRustBuilder::from_secret(secret(
+        "8",
+        "https://secret.test,https://mirror.test",
+        "https://hooks.test/reqwest",
+    ))
+    .unwrap()
+    .build_config()
+    .unwrap();
+
+    assert_eq!(config.retry_policy.max_attempts, 8);
+    assert_eq!(
+        config.domains,
+        [url("https://secret.test"), url("https://mirror.test")]
+    );
+    assert_eq!(
+        config.webhooks.reqwest_error,
+        Some(url("https://hooks.test/reqwest"))
+    );
+    assert!(config.webhooks.utils_error.is_none());
+    assert_eq!(
+        config.proxy_sources,
+        [
+            ProxySource::from_location("https://lists.test/socks5.txt", ProxyKind::Socks5),
+            ProxySource::from_location("http.txt", ProxyKind::Http),
+        ]
+    );
+    assert_eq!(config.headers[header::USER_AGENT], "secret agent");
+    assert_eq!(config.headers[header::ACCEPT], "text/html");
+    assert!(config.headers.get(header::ACCEPT_ENCODING).is_none());
+}
+
+#[test]
+fn empty_secret_values_keep_the_defaults() {
+    let config = HiAnimeRustBuilder::from_secret(secret("", " ", ""))
+        .unwrap()
+        .build_config()
+        .unwrap();
+
+    assert_eq!(
+        config.retry_policy.max_attempts,
+        RetryPolicy::default().max_attempts
+    );
+    assert_eq!(config.domains, [url("https://aniwatchtv.to")]);
+    assert!(config.webhooks.reqwest_error.is_none());
+}
+
+#[test]
+fn rejects_invalid_secret_values() {
+    for (secret, what) in [
+        (secret("ten", "", ""), "non numeric retries"),
+        (secret("", "not a url", ""), "bad domain"),
+        (secret("", "", "not a url"), "bad webhook"),
+    ] {
+        assert!(
+            matches!(
+                HiAnimeRustBuilder::from_secret(secret),
+                Err(AniRustError::InvalidConfig(_))
+            ),
+            "{}",
+            what
+        );
+    }
+
+    assert_invalid(
+        HiAnimeRustBuilder::from_secret(secret("", "ftp://secret.test", "")).unwrap(),
+        "ftp domain",
+    );
+}
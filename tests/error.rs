@@ -0,0 +1,223 @@
+use std::collections::HashSet;
+
+use aniscraper::{
+    config::Webhooks,
+    error::{AniRustError, DomainError},
+    parse::ParseDiagnostics,
+};
+use reqwest::{StatusCode, Url};
+
+// Webhook of each kind points at a URL named after the kind
+fn webhooks() -> Webhooks {
+    let hook = |kind: &str| Some(Url::parse(&format!("https://hooks.test/{}", kind)).unwrap());
+    Webhooks {
+        reqwest_error: hook("reqwest"),
+        no_proxies_available: hook("proxies"),
+        failed_to_fetch_after_retries: hook("retries"),
+        utils_error: hook("utils"),
+        unknown_error: hook("unknown"),
+    }
+}
+
+// Exhaustive on purpose: a new variant will not compile until it is named here
+// and, through `EVERY_VARIANT`, added to the table below
+fn variant(error: &AniRustError) -> &'static str {
+    match error {
+        AniRustError::ReqwestError(_) => "ReqwestError",
+        AniRustError::RegexError(_) => "RegexError",
+        AniRustError::NoProxiesAvailable => "NoProxiesAvailable",
+        AniRustError::FailedToFetchAfterRetries { .. } => "FailedToFetchAfterRetries",
+        AniRustError::AllDomainsFailed(_) => "AllDomainsFailed",
+        AniRustError::HttpStatus { .. } => "HttpStatus",
+        AniRustError::NotFound { .. } => "NotFound",
+        AniRustError::AntiBotChallenge { .. } => "AntiBotChallenge",
+        AniRustError::SelectorNotFound { .. } => "SelectorNotFound",
+        AniRustError::InvalidResponse { .. } => "InvalidResponse",
+        AniRustError::IncompleteParse { .. } => "IncompleteParse",
+        AniRustError::ExtractionFailed { .. } => "ExtractionFailed",
+        AniRustError::DecryptionKeyNotFound { .. } => "DecryptionKeyNotFound",
+        AniRustError::ParseIntError(_) => "ParseIntError",
+        AniRustError::NoDomainExists(_) => "NoDomainExists",
+        AniRustError::InvalidConfig(_) => "InvalidConfig",
+        AniRustError::NoRecordedResponse(_) => "NoRecordedResponse",
+        AniRustError::InvalidId { .. } => "InvalidId",
+        AniRustError::UnknownError(_) => "UnknownError",
+    }
+}
+
+const EVERY_VARIANT: usize = 19;
+
+fn url() -> String {
+    "https://hianime.test/home".to_string()
+}
+
+fn status(status: StatusCode) -> AniRustError {
+    AniRustError::HttpStatus { url: url(), status }
+}
+
+fn all_domains_failed(errors: Vec<AniRustError>) -> AniRustError {
+    AniRustError::AllDomainsFailed(
+        errors
+            .into_iter()
+            .map(|error| DomainError {
+                domain: "https://hianime.test".to_string(),
+                error,
+            })
+            .collect(),
+    )
+}
+
+async fn reqwest_errors() -> (AniRustError, AniRustError) {
+    let builder = reqwest::Client::new()
+        .get("http://[not-a-host")
+        .build()
+        .unwrap_err();
+    // Nothing listens on port 1, so the connection is refused
+    let connect = reqwest::get("http://127.0.0.1:1").await.unwrap_err();
+    (builder.into(), connect.into())
+}
+
+#[tokio::test]
+async fn every_variant_is_classified() {
+    let (builder_error, connect_error) = reqwest_errors().await;
+    let unclosed_group = String::from("(");
+    let regex_error = regex::Regex::new(&unclosed_group).unwrap_err();
+    let parse_int_error = "x".parse::<u32>().unwrap_err();
+
+    // Error, whether it is retryable and the webhook it is reported to
+    let table: Vec<(AniRustError, bool, Option<&str>)> = vec![
+        (builder_error, false, Some("reqwest")),
+        (connect_error, true, Some("reqwest")),
+        (AniRustError::RegexError(regex_error), false, Some("utils")),
+        (AniRustError::NoProxiesAvailable, true, Some("proxies")),
+        (
+            AniRustError::FailedToFetchAfterRetries {
+                url: url(),
+                attempts: 3,
+                last_error: None,
+            },
+            true,
+            Some("retries"),
+        ),
+        (
+            all_domains_failed(vec![AniRustError::NotFound { url: url() }]),
+            false,
+            Some("unknown"),
+        ),
+        (
+            all_domains_failed(vec![
+                AniRustError::NotFound { url: url() },
+                status(StatusCode::BAD_GATEWAY),
+            ]),
+            true,
+            Some("unknown"),
+        ),
+        (status(StatusCode::TOO_MANY_REQUESTS), true, Some("reqwest")),
+        (
+            status(StatusCode::SERVICE_UNAVAILABLE),
+            true,
+            Some("reqwest"),
+        ),
+        (status(StatusCode::FORBIDDEN), false, Some("reqwest")),
+        (
+            AniRustError::NotFound { url: url() },
+            false,
+            Some("reqwest"),
+        ),
+        (
+            AniRustError::AntiBotChallenge { url: url() },
+            true,
+            Some("reqwest"),
+        ),
+        (
+            AniRustError::SelectorNotFound {
+                url: url(),
+                selector: "#main".to_string(),
+            },
+            false,
+            Some("utils"),
+        ),
+        (
+            AniRustError::InvalidResponse {
+                url: url(),
+                reason: "not json".to_string(),
+            },
+            false,
+            Some("utils"),
+        ),
+        (
+            AniRustError::IncompleteParse {
+                url: url(),
+                diagnostics: ParseDiagnostics::default(),
+            },
+            false,
+            Some("utils"),
+        ),
+        (
+            AniRustError::ExtractionFailed {
+                server: "megacloud".to_string(),
+                reason: "no sources".to_string(),
+            },
+            false,
+            Some("unknown"),
+        ),
+        (
+            AniRustError::DecryptionKeyNotFound {
+                server: "megacloud".to_string(),
+            },
+            false,
+            Some("unknown"),
+        ),
+        (
+            AniRustError::ParseIntError(parse_int_error),
+            false,
+            Some("utils"),
+        ),
+        (
+            AniRustError::NoDomainExists("hianime.test".to_string()),
+            false,
+            None,
+        ),
+        (AniRustError::InvalidConfig("bad".to_string()), false, None),
+        (AniRustError::NoRecordedResponse(url()), false, None),
+        (
+            AniRustError::InvalidId {
+                id: "../admin".to_string(),
+                reason: "path separator".to_string(),
+            },
+            false,
+            None,
+        ),
+        (
+            AniRustError::UnknownError("boom".to_string()),
+            false,
+            Some("unknown"),
+        ),
+    ];
+
+    let webhooks = webhooks();
+    for (error, retryable, webhook) in &table {
+        assert_eq!(
+            error.is_retryable(),
+            *retryable,
+            "is_retryable of {:?}",
+            error
+        );
+        assert_eq!(
+            error.webhook_url(&webhooks).map(|url| url.path()),
+            webhook.map(|kind| format!("/{}", kind)).as_deref(),
+            "webhook of {:?}",
+            error
+        );
+    }
+
+    let covered: HashSet<&str> = table.iter().map(|(error, ..)| variant(error)).collect();
+    assert_eq!(covered.len(), EVERY_VARIANT);
+}
+
+#[test]
+fn unset_webhooks_report_nowhere() {
+    let error = AniRustError::UnknownError("boom".to_string());
+
+    assert!(error.webhook_url(&Webhooks::default()).is_none());
+}
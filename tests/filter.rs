@@ -0,0 +1,81 @@
+mod common;
+
+use std::sync::Arc;
+
+use aniscraper::filter::{
+    AiringStatus, AnimeType, FilterDate, Genre, Language, Rating, Score, SearchFilter, Season,
+    SortOrder,
+};
+use chrono::{Month, NaiveDate};
+
+use common::{html_fixture, ScriptedFetcher};
+
+#[test]
+fn empty_filter_sends_no_facets() {
+    assert!(SearchFilter::new().query_pairs().is_empty());
+}
+
+#[test]
+fn every_facet_maps_to_its_query_parameter() {
+    let filter = SearchFilter::new()
+        .keyword("one piece")
+        .anime_type(AnimeType::Tv)
+        .status(AiringStatus::CurrentlyAiring)
+        .rating(Rating::Pg13)
+        .score(Score::Good)
+        .season(Season::Fall)
+        .language(Language::SubAndDub)
+        .start_date(FilterDate::Month(1999, Month::October))
+        .end_date(NaiveDate::from_ymd_opt(2024, 3, 9).unwrap())
+        .sort(SortOrder::RecentlyUpdated)
+        .genres([
+            Genre::Action,
+            Genre::Adventure,
+            Genre::Action,
+            Genre::Isekai,
+        ]);
+
+    let pairs = filter.query_pairs();
+    let pairs: Vec<(&str, &str)> = pairs
+        .iter()
+        .map(|(name, value)| (*name, value.as_str()))
+        .collect();
+
+    assert_eq!(
+        pairs,
+        vec![
+            ("keyword", "one piece"),
+            ("type", "2"),
+            ("status", "2"),
+            ("rated", "3"),
+            ("score", "7"),
+            ("season", "3"),
+            ("language", "3"),
+            ("sy", "1999"),
+            ("sm", "10"),
+            ("ey", "2024"),
+            ("em", "3"),
+            ("ed", "9"),
+            ("sort", "recently_updated"),
+            ("genres", "1,2,44"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn scrape_filter_parses_the_results_page() {
+    let fetcher = Arc::new(ScriptedFetcher::body(html_fixture("search")));
+    let hianime = common::client(fetcher.clone()).await;
+
+    let filter = SearchFilter::new()
+        .anime_type(AnimeType::Tv)
+        .genres([Genre::Drama, Genre::Romance]);
+    let results = hianime.scrape_filter(&filter, 2).await.unwrap();
+
+    assert_eq!(
+        fetcher.urls(),
+        ["https://hianime.test/filter?type=2&genres=8%2C22&page=2"]
+    );
+    assert_eq!(results.current_page, 2);
+    assert!(!results.animes.is_empty());
+}
@@ -0,0 +1,376 @@
+mod common;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use aniscraper::{
+    error::AniRustError,
+    hianime::HiAnimeRust,
+    parse::{
+        parse_about_anime, parse_anime_qtip, parse_atoz, parse_category, parse_characters,
+        parse_episodes, parse_home, parse_next_episode, parse_schedule, parse_search,
+        parse_search_suggestions, parse_servers, ParseMode, Parsed,
+    },
+    replay::{Fixture, ReplayFetcher},
+    selectors::Selectors,
+};
+use chrono::{FixedOffset, NaiveDate, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+// Set to rewrite the golden files from the current parser output
+const UPDATE_GOLDEN: &str = "UPDATE_GOLDEN";
+
+fn fixtures() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+fn selectors() -> Arc<Selectors> {
+    Selectors::shared_default()
+}
+
+fn html(name: &str) -> String {
+    let path = fixtures().join("html").join(format!("{}.html", name));
+    std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("failed to read {}: {}", path.display(), e))
+}
+
+// Checks that the fixture parsed cleanly and that its data matches the golden file
+fn assert_golden<T: Serialize>(name: &str, parsed: &Parsed<T>) {
+    assert!(
+        parsed.diagnostics.is_clean(),
+        "{} parsed with issues: {}",
+        name,
+        parsed.diagnostics
+    );
+
+    let path = fixtures().join("golden").join(format!("{}.json", name));
+    let actual = serde_json::to_value(&parsed.data).unwrap();
+
+    if std::env::var_os(UPDATE_GOLDEN).is_some() {
+        let json = serde_json::to_string_pretty(&actual).unwrap();
+        std::fs::write(&path, json + "\n").unwrap();
+        return;
+    }
+
+    let golden = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "failed to read {}: {} (run with {}=1 to create it)",
+            path.display(),
+            e,
+            UPDATE_GOLDEN
+        )
+    });
+    let expected: Value = serde_json::from_str(&golden).unwrap();
+
+    assert_eq!(
+        actual,
+        expected,
+        "{} no longer matches {}; rerun with {}=1 if the change is intended",
+        name,
+        path.display(),
+        UPDATE_GOLDEN
+    );
+}
+
+#[test]
+fn home() {
+    let home = parse_home(&selectors(), &html("home"));
+
+    assert_eq!(home.data.spotlight_animes.len(), 2);
+    assert_eq!(home.data.featured.latest_completed_animes.len(), 5);
+    assert_golden("home", &home);
+}
+
+// Used to panic when the featured blocks held fewer than twenty entries
+#[test]
+fn home_with_short_featured_blocks() {
+    let dropped = [
+        "death-note",
+        "hunter-x-hunter",
+        "frieren",
+        "kaiju-no-8",
+        "oshi-no-ko-2",
+        "dungeon-meshi",
+        "solo-leveling",
+    ];
+    let page: String = html("home")
+        .lines()
+        .filter(|line| {
+            !dropped
+                .iter()
+                .any(|name| line.contains(&format!("img.test/featured/{}.jpg", name)))
+        })
+        .collect();
+    let featured = parse_home(&selectors(), &page).data.featured;
+
+    assert_eq!(featured.top_airing_animes.len(), 5);
+    assert_eq!(featured.most_popular_animes.len(), 5);
+    assert_eq!(featured.most_favorite_animes.len(), 3);
+    assert!(featured.latest_completed_animes.is_empty());
+}
+
+#[test]
+fn atoz() {
+    let atoz = parse_atoz(&selectors(), &html("atoz"), 1);
+
+    assert_eq!(atoz.data.total_pages, 207);
+    assert_golden("atoz", &atoz);
+}
+
+#[test]
+fn about_anime() {
+    let about = parse_about_anime(&selectors(), &html("about")).expect("details block");
+
+    assert_eq!(about.data.anime_id, 18542);
+    assert_eq!(about.data.characters.len(), 2);
+    assert_golden("about", &about);
+}
+
+// Category and duration are the last two tick items; the episode counts
+// before them used to be read as the duration
+#[test]
+fn about_anime_category_and_duration() {
+    let about = parse_about_anime(&selectors(), &html("about")).unwrap();
+    assert_eq!(about.data.category, "TV");
+    assert_eq!(about.data.duration, "24m");
+
+    // Movies have no episode counts
+    let page: String = html("about")
+        .lines()
+        .filter(|line| !line.contains(r#"">28</div>"#))
+        .collect();
+    let about = parse_about_anime(&selectors(), &page).unwrap();
+    assert_eq!(about.data.category, "TV");
+    assert_eq!(about.data.duration, "24m");
+}
+
+#[test]
+fn about_anime_without_details_block() {
+    assert!(parse_about_anime(&selectors(), &html("search")).is_none());
+}
+
+#[test]
+fn characters() {
+    let characters = parse_characters(&selectors(), &html("characters"), 1);
+
+    assert_eq!(characters.data.total_pages, 3);
+    assert_eq!(characters.data.characters[0].voice_actors.len(), 2);
+    assert!(characters.data.characters[1].voice_actors.is_empty());
+    assert_golden("characters", &characters);
+}
+
+#[test]
+fn anime_qtip() {
+    let qtip = parse_anime_qtip(&selectors(), &html("qtip"));
+
+    assert_eq!(qtip.data.id, "frieren-beyond-journeys-end-18542");
+    assert_eq!(qtip.data.genres, ["Adventure", "Drama", "Fantasy"]);
+    assert_golden("qtip", &qtip);
+}
+
+#[test]
+fn category() {
+    let category = parse_category(&selectors(), &html("category"), 3);
+
+    assert!(category.data.has_next_page);
+    assert_golden("category", &category);
+}
+
+#[test]
+fn search() {
+    let search = parse_search(&selectors(), &html("search"), 1);
+
+    assert!(!search.data.has_next_page);
+    assert_golden("search", &search);
+}
+
+// Pages past the last one, such as a search page without pagination, have no next page
+#[test]
+fn no_next_page_past_the_last_page() {
+    assert!(
+        !parse_atoz(&selectors(), &html("atoz"), 208)
+            .data
+            .has_next_page
+    );
+    assert!(
+        !parse_category(&selectors(), &html("category"), 97)
+            .data
+            .has_next_page
+    );
+    assert!(
+        !parse_search(&selectors(), &html("search"), 2)
+            .data
+            .has_next_page
+    );
+    assert!(
+        !parse_characters(&selectors(), &html("characters"), 4)
+            .data
+            .has_next_page
+    );
+}
+
+#[test]
+fn search_suggestions() {
+    let suggestions = parse_search_suggestions(&selectors(), &html("suggestions"));
+
+    assert_eq!(suggestions.data.len(), 3);
+    assert_eq!(suggestions.data[2].aired, "");
+    assert_eq!(suggestions.data[2].category, "Movie");
+    assert_golden("suggestions", &suggestions);
+}
+
+#[test]
+fn search_suggestions_without_match() {
+    let suggestions = parse_search_suggestions(&selectors(), "");
+
+    assert!(suggestions.data.is_empty());
+    assert!(suggestions.diagnostics.is_clean());
+}
+
+#[test]
+fn schedule() {
+    let ist = FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap();
+    let date = NaiveDate::from_ymd_opt(2024, 10, 20).unwrap();
+    let schedule = parse_schedule(&selectors(), &html("schedule"), date, ist);
+
+    assert_eq!(schedule.data.len(), 2);
+    assert_eq!(
+        schedule.data[1].airing_at.with_timezone(&Utc).to_rfc3339(),
+        "2024-10-20T12:20:00+00:00"
+    );
+    assert_golden("schedule", &schedule);
+}
+
+#[test]
+fn reports_unreadable_air_times() {
+    let page = html("schedule").replace("17:50", "TBA");
+    let date = NaiveDate::from_ymd_opt(2024, 10, 20).unwrap();
+    let schedule = parse_schedule(&selectors(), &page, date, FixedOffset::east_opt(0).unwrap());
+
+    assert_eq!(
+        schedule.diagnostics.invalid_numbers[0].field,
+        "schedule.airing_at"
+    );
+    assert_eq!(
+        schedule.data[1].airing_at.to_rfc3339(),
+        "2024-10-20T00:00:00+00:00"
+    );
+}
+
+#[test]
+fn next_episode() {
+    let next = parse_next_episode(&selectors(), &html("watch"));
+
+    assert!(next.diagnostics.is_clean());
+    assert_eq!(
+        next.data.map(|time| time.to_rfc3339()).as_deref(),
+        Some("2024-10-24T15:30:00+00:00")
+    );
+}
+
+#[test]
+fn next_episode_of_finished_show() {
+    let page = html("watch").replace("schedule-alert", "film-stats");
+    let next = parse_next_episode(&selectors(), &page);
+
+    assert!(next.diagnostics.is_clean());
+    assert!(next.data.is_none());
+}
+
+#[test]
+fn episodes() {
+    let episodes = parse_episodes(&selectors(), &html("episodes"));
+
+    assert_eq!(episodes.data.total_episodes, 3);
+    assert_golden("episodes", &episodes);
+}
+
+#[test]
+fn servers() {
+    let servers = parse_servers(&selectors(), &html("servers"));
+
+    assert_eq!(servers.data.episode_no, 2);
+    assert_golden("servers", &servers);
+}
+
+#[test]
+fn reports_missing_selectors() {
+    let parsed = parse_home(&selectors(), &html("atoz"));
+
+    assert!(!parsed.diagnostics.is_clean());
+    assert!(parsed
+        .diagnostics
+        .missing_selectors
+        .iter()
+        .any(|selector| selector.contains("#slider")));
+}
+
+#[test]
+fn reports_empty_fields() {
+    let page = html("home").replace(
+        "desi-head-title dynamic-name",
+        "desi-head-title dynamic-title",
+    );
+    let parsed = parse_home(&selectors(), &page);
+
+    assert!(parsed.diagnostics.missing_selectors.is_empty());
+    assert_eq!(
+        parsed.diagnostics.empty_fields,
+        vec!["spotlight_animes.title"]
+    );
+}
+
+#[test]
+fn reports_invalid_numbers() {
+    let page = html("servers").replace("data-server-id=\"1\"", "data-server-id=\"one\"");
+    let parsed = parse_servers(&selectors(), &page);
+
+    assert_eq!(parsed.diagnostics.invalid_numbers.len(), 1);
+    assert_eq!(parsed.diagnostics.invalid_numbers[0].field, "sub.server_id");
+    assert_eq!(parsed.diagnostics.invalid_numbers[0].text, "one");
+    assert_eq!(parsed.data.sub[1].server_id, 0);
+}
+
+async fn serving_atoz_as_home(mode: ParseMode) -> HiAnimeRust {
+    let fixture = Fixture {
+        url: "https://hianime.test/home".to_string(),
+        request_headers: vec![],
+        status: 200,
+        headers: vec![],
+        body: html("atoz"),
+    };
+
+    common::builder(Arc::new(ReplayFetcher::from_fixtures(vec![fixture])))
+        .parse_mode(mode)
+        .build()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn lenient_mode_returns_incomplete_pages() {
+    let home = serving_atoz_as_home(ParseMode::Lenient)
+        .await
+        .scrape_home()
+        .await
+        .unwrap();
+
+    assert!(home.spotlight_animes.is_empty());
+}
+
+#[tokio::test]
+async fn strict_mode_rejects_incomplete_pages() {
+    let result = serving_atoz_as_home(ParseMode::Strict)
+        .await
+        .scrape_home()
+        .await;
+
+    match result {
+        Err(AniRustError::IncompleteParse { url, diagnostics }) => {
+            assert_eq!(url, "https://hianime.test/home");
+            assert!(!diagnostics.missing_selectors.is_empty());
+        }
+        other => panic!("expected IncompleteParse, got {:?}", other.map(|_| ())),
+    }
+}
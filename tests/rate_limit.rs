@@ -0,0 +1,117 @@
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use aniscraper::{
+    rate_limit::{RateLimit, RateLimitConfig, RateLimiter},
+    retry::RetryPolicy,
+};
+use reqwest::StatusCode;
+use tokio::time::Instant;
+
+use common::{response, ScriptedFetcher};
+
+const URL: &str = "https://hianime.test/home";
+
+fn limiter(requests_per_second: f64, burst: u32, max_in_flight: usize) -> RateLimiter {
+    RateLimiter::new(RateLimitConfig::new(RateLimit {
+        requests_per_second,
+        burst,
+        max_in_flight,
+    }))
+}
+
+#[tokio::test(start_paused = true)]
+async fn burst_is_sent_at_once_then_tokens_refill_at_the_rate() {
+    let limiter = limiter(2.0, 3, 16);
+    let started = Instant::now();
+
+    for _ in 0..3 {
+        limiter.acquire(URL).await;
+    }
+    assert_eq!(started.elapsed(), Duration::ZERO);
+
+    limiter.acquire(URL).await;
+    assert_eq!(started.elapsed(), Duration::from_millis(500));
+    limiter.acquire(URL).await;
+    assert_eq!(started.elapsed(), Duration::from_secs(1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn idle_time_refills_no_more_than_the_burst() {
+    let limiter = limiter(1.0, 2, 16);
+    limiter.acquire(URL).await;
+    limiter.acquire(URL).await;
+
+    tokio::time::sleep(Duration::from_secs(60)).await;
+    let started = Instant::now();
+    limiter.acquire(URL).await;
+    limiter.acquire(URL).await;
+    assert_eq!(started.elapsed(), Duration::ZERO);
+
+    limiter.acquire(URL).await;
+    assert_eq!(started.elapsed(), Duration::from_secs(1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn hosts_are_limited_separately() {
+    let limiter = RateLimiter::new(RateLimitConfig::unlimited().host(
+        "hianime.test",
+        RateLimit {
+            requests_per_second: 1.0,
+            burst: 1,
+            max_in_flight: 16,
+        },
+    ));
+    let started = Instant::now();
+
+    limiter.acquire(URL).await;
+    for _ in 0..10 {
+        limiter.acquire("https://megacloud.test/embed").await;
+    }
+    assert_eq!(started.elapsed(), Duration::ZERO);
+}
+
+#[tokio::test(start_paused = true)]
+async fn in_flight_requests_are_capped() {
+    let limiter = limiter(1000.0, 100, 2);
+
+    let first = limiter.acquire(URL).await;
+    let _second = limiter.acquire(URL).await;
+    let third = tokio::time::timeout(Duration::from_secs(10), limiter.acquire(URL)).await;
+    assert!(third.is_err());
+
+    drop(first);
+    let third = tokio::time::timeout(Duration::from_secs(10), limiter.acquire(URL)).await;
+    assert!(third.is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+async fn waiting_on_the_limiter_counts_against_the_deadline() {
+    let fetcher = Arc::new(ScriptedFetcher::new(response(
+        StatusCode::SERVICE_UNAVAILABLE,
+        "",
+    )));
+    let hianime = common::builder(fetcher.clone())
+        .retry_policy(RetryPolicy {
+            max_attempts: 5,
+            deadline: Some(Duration::from_secs(2)),
+            ..RetryPolicy::default()
+        })
+        .rate_limit(RateLimitConfig::new(RateLimit {
+            requests_per_second: 0.1,
+            burst: 1,
+            max_in_flight: 16,
+        }))
+        .build()
+        .await
+        .unwrap();
+    let started = Instant::now();
+
+    assert!(hianime.scrape_home().await.is_err());
+
+    // The next token is 10s away, far past the deadline
+    assert_eq!(fetcher.requests().len(), 1);
+    assert!(started.elapsed() <= Duration::from_secs(2));
+}
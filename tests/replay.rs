@@ -0,0 +1,130 @@
+mod common;
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use aniscraper::{
+    error::AniRustError,
+    replay::{Fixture, RecordingFetcher, ReplayFetcher},
+    retry::RetryPolicy,
+    transport::{FetchRequest, Fetcher},
+};
+use reqwest::header::HeaderMap;
+
+use common::{client, temp_dir, ScriptedFetcher};
+
+const ATOZ_HTML: &str = r#"<html><body>
+<div id="main-wrapper"><div><div class="page-az-wrap"><section><div class="tab-content"><div>
+  <div class="film_list-wrap">
+    <div class="flw-item">
+      <div class="film-poster"><img class="film-poster-img" data-src="https://img.test/one-piece.jpg"></div>
+      <div class="film-detail"><h3 class="film-name"><a class="dynamic-name" href="/one-piece-100">One Piece</a></h3></div>
+    </div>
+  </div>
+</div></div></section></div></div></div>
+</body></html>"#;
+
+#[tokio::test]
+async fn replays_recorded_responses() {
+    let dir = temp_dir("replay");
+
+    let recorder = RecordingFetcher::new(Arc::new(ScriptedFetcher::body(ATOZ_HTML)), &dir).unwrap();
+    let recorded = client(Arc::new(recorder))
+        .await
+        .scrape_atoz(1)
+        .await
+        .unwrap();
+    assert_eq!(recorded.animes.len(), 1);
+    assert_eq!(recorded.animes[0].id, "one-piece-100");
+    assert_eq!(recorded.animes[0].title, "One Piece");
+
+    let replay = ReplayFetcher::new(&dir).unwrap();
+    assert_eq!(replay.len(), 1);
+    let replayed = client(Arc::new(replay)).await.scrape_atoz(1).await.unwrap();
+
+    assert_eq!(
+        serde_json::to_value(&recorded).unwrap(),
+        serde_json::to_value(&replayed).unwrap()
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn replay_fails_for_unrecorded_urls() {
+    let dir = temp_dir("replay-missing");
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let replay = ReplayFetcher::new(&dir).unwrap();
+    let hianime = common::builder(Arc::new(replay))
+        .retry_policy(RetryPolicy::default())
+        .build()
+        .await
+        .unwrap();
+
+    // The default retry policy must not retry a missing fixture
+    let started = Instant::now();
+    let result = hianime.scrape_category("tv", 1).await;
+    assert!(started.elapsed() < Duration::from_secs(1));
+    match result {
+        Err(AniRustError::AllDomainsFailed(errors)) => assert!(matches!(
+            errors[0].error,
+            AniRustError::NoRecordedResponse(ref url) if url == "https://hianime.test/tv?page=1"
+        )),
+        other => panic!("expected a missing fixture, got {:?}", other.map(|_| ())),
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+fn fixture(url: &str, body: &str) -> Fixture {
+    Fixture {
+        url: url.to_string(),
+        request_headers: vec![],
+        status: 200,
+        headers: vec![],
+        body: body.to_string(),
+    }
+}
+
+fn request(url: &str) -> FetchRequest {
+    FetchRequest {
+        url: url.to_string(),
+        headers: HeaderMap::new(),
+        proxy: None,
+        timeout: Duration::from_secs(1),
+    }
+}
+
+#[tokio::test]
+async fn replay_matches_the_query_exactly() {
+    let replay = ReplayFetcher::from_fixtures(vec![
+        fixture("https://hianime.test/search?keyword=one&page=1", "one"),
+        fixture("https://hianime.test/search?keyword=two&page=1", "two"),
+    ]);
+
+    let response = replay
+        .fetch(request("https://hianime.test/search?keyword=two&page=1"))
+        .await
+        .unwrap();
+    assert_eq!(response.body, "two");
+
+    let missing = replay
+        .fetch(request("https://hianime.test/search?keyword=two&page=2"))
+        .await;
+    assert!(matches!(missing, Err(AniRustError::NoRecordedResponse(_))));
+}
+
+#[tokio::test]
+async fn replay_ignores_cache_busting_parameters() {
+    let replay = ReplayFetcher::from_fixtures(vec![
+        fixture("https://megacloud.test/player.js?v=200", "newer"),
+        fixture("https://megacloud.test/player.js?v=100", "older"),
+    ]);
+
+    let response = replay
+        .fetch(request("https://megacloud.test/player.js?v=300"))
+        .await
+        .unwrap();
+    assert_eq!(response.body, "newer");
+}
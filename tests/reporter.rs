@@ -0,0 +1,126 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use aniscraper::{
+    error::AniRustError, hianime::HiAnimeRust, parse::ParseMode, reporter::ErrorReporter,
+    retry::RetryPolicy,
+};
+use reqwest::StatusCode;
+
+use common::{html_fixture, response, ScriptedFetcher};
+
+// Keeps the debug output of every reported error
+#[derive(Debug, Default)]
+struct RecordingReporter {
+    reports: Mutex<Vec<String>>,
+}
+
+impl RecordingReporter {
+    fn reports(&self) -> Vec<String> {
+        self.reports.lock().unwrap().clone()
+    }
+}
+
+impl ErrorReporter for RecordingReporter {
+    fn report(&self, error: &AniRustError) {
+        self.reports.lock().unwrap().push(format!("{:?}", error));
+    }
+}
+
+async fn client(
+    fetcher: ScriptedFetcher,
+    parse_mode: ParseMode,
+) -> (HiAnimeRust, Arc<RecordingReporter>) {
+    let reporter = Arc::new(RecordingReporter::default());
+    let hianime = common::builder(Arc::new(fetcher))
+        .retry_policy(RetryPolicy {
+            max_attempts: 2,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        })
+        .parse_mode(parse_mode)
+        .error_reporter(reporter.clone())
+        .build()
+        .await
+        .unwrap();
+    (hianime, reporter)
+}
+
+#[tokio::test]
+async fn returned_errors_are_reported_once() {
+    let fetcher = ScriptedFetcher::new(response(StatusCode::NOT_FOUND, ""));
+    let (hianime, reporter) = client(fetcher, ParseMode::Lenient).await;
+
+    let result = hianime.scrape_about_anime("one-piece-100").await;
+
+    assert!(matches!(result, Err(AniRustError::NotFound { .. })));
+    let reports = reporter.reports();
+    assert_eq!(reports.len(), 1);
+    assert!(reports[0].starts_with("NotFound"), "{:?}", reports);
+}
+
+#[tokio::test]
+async fn rejected_ids_are_reported() {
+    let (hianime, reporter) = client(ScriptedFetcher::body(""), ParseMode::Lenient).await;
+
+    assert!(hianime.scrape_about_anime("../admin").await.is_err());
+
+    let reports = reporter.reports();
+    assert_eq!(reports.len(), 1);
+    assert!(reports[0].starts_with("InvalidId"), "{:?}", reports);
+}
+
+#[tokio::test]
+async fn only_the_final_error_is_reported_when_every_attempt_fails() {
+    let fetcher = ScriptedFetcher::new(response(StatusCode::SERVICE_UNAVAILABLE, ""));
+    let (hianime, reporter) = client(fetcher, ParseMode::Lenient).await;
+
+    assert!(hianime.scrape_atoz(1).await.is_err());
+
+    let reports = reporter.reports();
+    assert_eq!(reports.len(), 1);
+    assert!(reports[0].starts_with("AllDomainsFailed"), "{:?}", reports);
+}
+
+#[tokio::test]
+async fn recovered_failures_are_not_reported() {
+    let fetcher = ScriptedFetcher::body(html_fixture("atoz"))
+        .then(Ok(response(StatusCode::SERVICE_UNAVAILABLE, "")));
+    let (hianime, reporter) = client(fetcher, ParseMode::Lenient).await;
+
+    hianime.scrape_atoz(1).await.unwrap();
+
+    assert!(reporter.reports().is_empty());
+}
+
+#[tokio::test]
+async fn report_mode_reports_incomplete_pages_and_returns_them() {
+    let (hianime, reporter) = client(
+        ScriptedFetcher::body(html_fixture("atoz")),
+        ParseMode::Report,
+    )
+    .await;
+
+    // The A-Z page has none of the home page's sections
+    hianime.scrape_home().await.unwrap();
+
+    let reports = reporter.reports();
+    assert_eq!(reports.len(), 1);
+    assert!(reports[0].starts_with("IncompleteParse"), "{:?}", reports);
+}
+
+#[tokio::test]
+async fn lenient_mode_reports_nothing_for_incomplete_pages() {
+    let (hianime, reporter) = client(
+        ScriptedFetcher::body(html_fixture("atoz")),
+        ParseMode::Lenient,
+    )
+    .await;
+
+    hianime.scrape_home().await.unwrap();
+
+    assert!(reporter.reports().is_empty());
+}
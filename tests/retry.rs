@@ -0,0 +1,96 @@
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use aniscraper::{
+    error::AniRustError,
+    proxy::{ProxyKind, ProxySource},
+    retry::RetryPolicy,
+};
+
+use common::{html_fixture, temp_dir, ScriptedFetcher};
+
+fn policy(jitter: f64) -> RetryPolicy {
+    RetryPolicy {
+        base_delay: Duration::from_millis(250),
+        max_delay: Duration::from_secs(10),
+        jitter,
+        ..RetryPolicy::default()
+    }
+}
+
+#[test]
+fn delay_doubles_after_each_attempt_up_to_the_cap() {
+    let policy = policy(0.0);
+    let delays: Vec<u128> = (1..=8).map(|n| policy.delay(n).as_millis()).collect();
+
+    assert_eq!(delays, [250, 500, 1000, 2000, 4000, 8000, 10_000, 10_000]);
+}
+
+#[test]
+fn delay_does_not_overflow_on_late_attempts() {
+    let policy = policy(0.0);
+
+    assert_eq!(policy.delay(0), Duration::from_millis(250));
+    assert_eq!(policy.delay(usize::MAX), Duration::from_secs(10));
+}
+
+#[test]
+fn jitter_only_shortens_the_delay_by_up_to_its_fraction() {
+    let jittered = policy(0.5);
+
+    for attempt in 1..=8 {
+        let full = policy(0.0).delay(attempt);
+        for _ in 0..200 {
+            let delay = jittered.delay(attempt);
+            assert!(delay <= full, "{:?} exceeds {:?}", delay, full);
+            assert!(delay >= full / 2, "{:?} is below half of {:?}", delay, full);
+        }
+    }
+}
+
+#[test]
+fn default_policy_has_a_deadline() {
+    let policy = RetryPolicy::default();
+
+    assert!(policy
+        .deadline
+        .is_some_and(|deadline| deadline <= Duration::from_secs(60)));
+}
+
+#[tokio::test]
+async fn fetcher_errors_penalize_the_proxy_and_are_retried() {
+    let dir = temp_dir("retry-proxies");
+    std::fs::create_dir_all(&dir).unwrap();
+    let proxies = dir.join("proxies.txt");
+    std::fs::write(&proxies, "1.2.3.4:8080\n").unwrap();
+
+    let fetcher = Arc::new(
+        ScriptedFetcher::body(html_fixture("atoz"))
+            .then(Err(AniRustError::UnknownError("broken proxy".to_string()))),
+    );
+    let hianime = common::builder(fetcher.clone())
+        .retry_policy(RetryPolicy {
+            max_attempts: 2,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        })
+        .proxy_source(ProxySource::File {
+            path: proxies,
+            kind: ProxyKind::Http,
+        })
+        .build()
+        .await
+        .unwrap();
+
+    hianime.scrape_atoz(1).await.unwrap();
+
+    assert_eq!(fetcher.requests().len(), 2);
+    let stats = hianime.transport().proxies().stats();
+    assert_eq!(stats[0].1.failures, 1);
+    assert_eq!(stats[0].1.successes, 1);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
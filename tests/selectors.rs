@@ -0,0 +1,72 @@
+use std::path::PathBuf;
+
+use aniscraper::{
+    error::AniRustError,
+    parse::parse_home,
+    selectors::{SelectorProfile, Selectors},
+};
+
+fn home_html() -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/html/home.html");
+    std::fs::read_to_string(path).unwrap()
+}
+
+#[test]
+fn default_profile_round_trips_through_json_and_toml() {
+    let profile = SelectorProfile::default();
+
+    let json = serde_json::to_string(&profile).unwrap();
+    assert_eq!(SelectorProfile::from_json(&json).unwrap(), profile);
+
+    let toml = toml::to_string(&profile).unwrap();
+    assert_eq!(SelectorProfile::from_toml(&toml).unwrap(), profile);
+}
+
+#[test]
+fn partial_profile_overrides_only_its_selectors() {
+    let profile =
+        SelectorProfile::from_toml(r#"spotlight_title = ".deslide-item-content .desi-head-title""#)
+            .unwrap();
+
+    assert_eq!(
+        profile.spotlight_title,
+        ".deslide-item-content .desi-head-title"
+    );
+    assert_eq!(profile.trending, SelectorProfile::default().trending);
+}
+
+#[test]
+fn patched_profile_recovers_renamed_markup() {
+    let page = home_html().replace("desi-head-title dynamic-name", "desi-title");
+
+    let broken = parse_home(&Selectors::shared_default(), &page);
+    assert_eq!(
+        broken.diagnostics.empty_fields,
+        vec!["spotlight_animes.title"]
+    );
+
+    let profile =
+        SelectorProfile::from_json(r#"{ "spotlight_title": ".deslide-item-content .desi-title" }"#)
+            .unwrap();
+    let patched = parse_home(&Selectors::new(profile).unwrap(), &page);
+
+    assert!(patched.diagnostics.is_clean(), "{}", patched.diagnostics);
+    assert_eq!(patched.data.spotlight_animes[0].title, "One Piece");
+}
+
+#[test]
+fn rejects_invalid_selectors_and_unknown_fields() {
+    let profile = SelectorProfile {
+        trending: "#anime-trending >> .slide".to_string(),
+        ..SelectorProfile::default()
+    };
+    assert!(matches!(
+        Selectors::new(profile),
+        Err(AniRustError::InvalidConfig(reason)) if reason.contains("trending")
+    ));
+
+    assert!(matches!(
+        SelectorProfile::from_json(r#"{ "trendingg": ".slide" }"#),
+        Err(AniRustError::InvalidConfig(_))
+    ));
+}
@@ -0,0 +1,23 @@
+use aniscraper::servers::{AnimeServer, EpisodeType};
+
+#[test]
+fn server_names_round_trip() {
+    for server in [
+        AnimeServer::Vidstreaming,
+        AnimeServer::Megacloud,
+        AnimeServer::Streamsb,
+        AnimeServer::Streamtape,
+        AnimeServer::Vidcloud,
+    ] {
+        assert_eq!(server.as_str().parse::<AnimeServer>(), Ok(server));
+    }
+    assert_eq!("unknown".parse(), Ok(AnimeServer::Vidstreaming));
+}
+
+#[test]
+fn episode_types_round_trip() {
+    for kind in [EpisodeType::Sub, EpisodeType::Dub, EpisodeType::Raw] {
+        assert_eq!(kind.as_str().parse::<EpisodeType>(), Ok(kind));
+    }
+    assert_eq!("unknown".parse(), Ok(EpisodeType::Sub));
+}
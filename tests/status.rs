@@ -0,0 +1,161 @@
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use aniscraper::{
+    error::AniRustError, hianime::HiAnimeRust, retry::RetryPolicy, transport::FetchResponse,
+};
+use chrono::Utc;
+use reqwest::{
+    header::{HeaderValue, RETRY_AFTER},
+    StatusCode,
+};
+use tokio::time::Instant;
+
+use common::{html_fixture, response, ScriptedFetcher};
+
+const ATTEMPTS: usize = 3;
+
+async fn client(fetcher: Arc<ScriptedFetcher>) -> HiAnimeRust {
+    common::builder(fetcher)
+        .retry_policy(RetryPolicy {
+            max_attempts: ATTEMPTS,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(1),
+            jitter: 0.0,
+            deadline: None,
+            ..RetryPolicy::default()
+        })
+        .build()
+        .await
+        .unwrap()
+}
+
+// Page that parses, sent once the scripted failures are used up
+fn atoz_fetcher() -> ScriptedFetcher {
+    ScriptedFetcher::body(html_fixture("atoz"))
+}
+
+fn status(status: u16) -> Result<FetchResponse, AniRustError> {
+    Ok(response(StatusCode::from_u16(status).unwrap(), ""))
+}
+
+fn too_many_requests(retry_after: &str) -> Result<FetchResponse, AniRustError> {
+    let mut response = response(StatusCode::TOO_MANY_REQUESTS, "");
+    response
+        .headers
+        .insert(RETRY_AFTER, HeaderValue::from_str(retry_after).unwrap());
+    Ok(response)
+}
+
+// Error behind the `AllDomainsFailed` of the only configured domain
+fn domain_error(result: Result<impl Sized, AniRustError>) -> AniRustError {
+    match result {
+        Err(AniRustError::AllDomainsFailed(mut errors)) => errors.remove(0).error,
+        Err(other) => panic!("expected AllDomainsFailed, got {:?}", other),
+        Ok(_) => panic!("expected an error"),
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn not_found_is_not_retried() {
+    let fetcher = Arc::new(atoz_fetcher().then(status(404)));
+
+    let result = client(fetcher.clone()).await.scrape_atoz(1).await;
+
+    assert!(matches!(result, Err(AniRustError::NotFound { .. })));
+    assert_eq!(fetcher.requests().len(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn other_client_errors_are_not_retried() {
+    let fetcher = Arc::new(atoz_fetcher().then(status(403)));
+
+    let result = client(fetcher.clone()).await.scrape_atoz(1).await;
+
+    assert!(matches!(
+        domain_error(result),
+        AniRustError::HttpStatus { status, .. } if status == StatusCode::FORBIDDEN
+    ));
+    assert_eq!(fetcher.requests().len(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn server_errors_are_retried() {
+    let fetcher = Arc::new(atoz_fetcher().then(status(500)).then(status(502)));
+
+    let atoz = client(fetcher.clone()).await.scrape_atoz(1).await.unwrap();
+
+    assert!(!atoz.animes.is_empty());
+    assert_eq!(fetcher.requests().len(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn server_errors_fail_once_attempts_run_out() {
+    let fetcher = Arc::new(ScriptedFetcher::new(response(
+        StatusCode::SERVICE_UNAVAILABLE,
+        "",
+    )));
+
+    let result = client(fetcher.clone()).await.scrape_atoz(1).await;
+
+    match domain_error(result) {
+        AniRustError::FailedToFetchAfterRetries {
+            attempts,
+            last_error,
+            ..
+        } => {
+            assert_eq!(attempts, ATTEMPTS);
+            assert!(matches!(
+                last_error.as_deref(),
+                Some(AniRustError::HttpStatus { status, .. })
+                    if *status == StatusCode::SERVICE_UNAVAILABLE
+            ));
+        }
+        other => panic!("expected FailedToFetchAfterRetries, got {:?}", other),
+    }
+    assert_eq!(fetcher.requests().len(), ATTEMPTS);
+}
+
+#[tokio::test(start_paused = true)]
+async fn retry_after_in_seconds_is_honoured() {
+    let fetcher = Arc::new(atoz_fetcher().then(too_many_requests("7")));
+    let hianime = client(fetcher.clone()).await;
+    let started = Instant::now();
+
+    hianime.scrape_atoz(1).await.unwrap();
+
+    assert_eq!(fetcher.requests().len(), 2);
+    assert_eq!(started.elapsed(), Duration::from_secs(7));
+}
+
+#[tokio::test(start_paused = true)]
+async fn retry_after_as_a_date_is_honoured() {
+    let date = (Utc::now() + chrono::Duration::seconds(30)).to_rfc2822();
+    let fetcher = Arc::new(atoz_fetcher().then(too_many_requests(&date)));
+    let hianime = client(fetcher.clone()).await;
+    let started = Instant::now();
+
+    hianime.scrape_atoz(1).await.unwrap();
+
+    assert_eq!(fetcher.requests().len(), 2);
+    // The date only has second precision
+    let waited = started.elapsed();
+    assert!(
+        waited > Duration::from_secs(28) && waited <= Duration::from_secs(30),
+        "waited {:?}",
+        waited
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn retry_after_is_capped_at_a_minute() {
+    let fetcher = Arc::new(atoz_fetcher().then(too_many_requests("3600")));
+    let hianime = client(fetcher.clone()).await;
+    let started = Instant::now();
+
+    hianime.scrape_atoz(1).await.unwrap();
+
+    assert_eq!(started.elapsed(), Duration::from_secs(60));
+}
@@ -0,0 +1,65 @@
+use std::time::Duration;
+
+use aniscraper::{
+    proxy::{Proxy, ProxyKind},
+    transport::{FetchRequest, Fetcher, ReqwestFetcher},
+};
+use reqwest::header::HeaderMap;
+
+// Nothing listens on port 1, so every request fails right after its client is pooled
+fn proxy(host: &str) -> Proxy {
+    Proxy::new(ProxyKind::Http, &format!("{}:1", host))
+}
+
+async fn fetch_through(fetcher: &ReqwestFetcher, proxy: &Proxy) {
+    let request = FetchRequest {
+        url: "http://hianime.test/home".to_string(),
+        headers: HeaderMap::new(),
+        proxy: Some(proxy.clone()),
+        timeout: Duration::from_secs(5),
+    };
+    assert!(fetcher.fetch(request).await.is_err());
+    // Keeps the last use of each client apart
+    tokio::time::sleep(Duration::from_millis(5)).await;
+}
+
+fn sorted(mut proxies: Vec<Proxy>) -> Vec<String> {
+    proxies.sort_by(|a, b| a.address.cmp(&b.address));
+    proxies.into_iter().map(|proxy| proxy.address).collect()
+}
+
+#[tokio::test]
+async fn proxy_clients_are_reused() {
+    let fetcher = ReqwestFetcher::with_proxy_client_capacity(4);
+    let a = proxy("127.0.0.1");
+
+    fetch_through(&fetcher, &a).await;
+    fetch_through(&fetcher, &a).await;
+
+    assert_eq!(fetcher.cached_proxies(), [a]);
+}
+
+#[tokio::test]
+async fn least_recently_used_proxy_client_is_evicted_over_capacity() {
+    let fetcher = ReqwestFetcher::with_proxy_client_capacity(2);
+    let (a, b, c) = (proxy("127.0.0.1"), proxy("127.0.0.2"), proxy("127.0.0.3"));
+
+    fetch_through(&fetcher, &a).await;
+    fetch_through(&fetcher, &b).await;
+    // Using `a` again leaves `b` as the least recently used
+    fetch_through(&fetcher, &a).await;
+    fetch_through(&fetcher, &c).await;
+
+    assert_eq!(
+        sorted(fetcher.cached_proxies()),
+        ["127.0.0.1:1", "127.0.0.3:1"]
+    );
+
+    // Clones share the pooled clients
+    let clone = fetcher.clone();
+    fetch_through(&clone, &b).await;
+    assert_eq!(
+        sorted(fetcher.cached_proxies()),
+        ["127.0.0.2:1", "127.0.0.3:1"]
+    );
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use std::sync::Arc;
+
+use aniscraper::{error::AniRustError, hianime::HiAnimeRust};
+use chrono::{FixedOffset, NaiveDate};
+
+use common::ScriptedFetcher;
+
+// Empty AJAX payload, which the page parsers read as an empty page
+const EMPTY_AJAX: &str = r#"{"status":true,"html":""}"#;
+
+async fn client(recorder: Arc<ScriptedFetcher>) -> HiAnimeRust {
+    common::client(recorder).await
+}
+
+#[tokio::test]
+async fn search_query_is_percent_encoded() {
+    let recorder = Arc::new(ScriptedFetcher::body(EMPTY_AJAX));
+    let hianime = client(recorder.clone()).await;
+
+    hianime
+        .scrape_search("Re:Zero & #1? 進撃", 2)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        recorder.urls(),
+        ["https://hianime.test/search?keyword=Re%3AZero+%26+%231%3F+%E9%80%B2%E6%92%83&page=2"]
+    );
+}
+
+#[tokio::test]
+async fn ids_are_encoded_as_a_single_path_segment() {
+    let recorder = Arc::new(ScriptedFetcher::body(EMPTY_AJAX));
+    let hianime = client(recorder.clone()).await;
+
+    let _ = hianime.scrape_about_anime("re-zero?x#y 100").await;
+    let _ = hianime.scrape_category("tv", 3).await;
+    let _ = hianime.scrape_category("genre/action", 1).await;
+    let _ = hianime.scrape_next_episode_schedule("dandadan-19319").await;
+    let _ = hianime.scrape_anime_qtip(18542).await;
+
+    assert_eq!(
+        recorder.urls(),
+        [
+            "https://hianime.test/re-zero%3Fx%23y%20100",
+            "https://hianime.test/tv?page=3",
+            "https://hianime.test/genre/action?page=1",
+            "https://hianime.test/watch/dandadan-19319",
+            "https://hianime.test/ajax/movie/qtip/18542",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn ids_with_path_separators_are_rejected() {
+    let recorder = Arc::new(ScriptedFetcher::body(EMPTY_AJAX));
+    let hianime = client(recorder.clone()).await;
+
+    for result in [
+        hianime.scrape_about_anime("../admin").await.map(|_| ()),
+        hianime.scrape_category("tv\\..", 1).await.map(|_| ()),
+        hianime
+            .scrape_category("genre/../admin", 1)
+            .await
+            .map(|_| ()),
+        hianime
+            .scrape_category("genre//action", 1)
+            .await
+            .map(|_| ()),
+        hianime.scrape_episodes("").await.map(|_| ()),
+        hianime
+            .scrape_servers("one-piece-100?ep=1/2")
+            .await
+            .map(|_| ()),
+    ] {
+        assert!(matches!(result, Err(AniRustError::InvalidId { .. })));
+    }
+    assert!(recorder.urls().is_empty());
+}
+
+#[tokio::test]
+async fn ajax_parameters_are_encoded() {
+    let recorder = Arc::new(ScriptedFetcher::body(EMPTY_AJAX));
+    let hianime = client(recorder.clone()).await;
+
+    hianime.scrape_search_suggestions("Re:Zero").await.unwrap();
+    let schedule = hianime
+        .scrape_schedule(
+            NaiveDate::from_ymd_opt(2024, 10, 20).unwrap(),
+            FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let characters = hianime
+        .scrape_characters("frieren-beyond-journeys-end-18542", 2)
+        .await
+        .unwrap();
+
+    assert!(schedule.is_empty());
+    assert!(characters.characters.is_empty());
+    assert_eq!(
+        recorder.urls(),
+        [
+            "https://hianime.test/ajax/search/suggest?keyword=Re%3AZero",
+            "https://hianime.test/ajax/schedule/list?tzOffset=-330&date=2024-10-20",
+            "https://hianime.test/ajax/character/list/18542?page=2",
+        ]
+    );
+}